@@ -7,13 +7,38 @@ use std::convert::TryInto;
 pub struct LockedBalance {
     pub amount: Balance,
     pub release_at: Timestamp,
+    pub token_id: Option<AccountId>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum EarningKind {
+    Payment,
+    Tip,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Earning {
+    pub task_id: TaskId,
+    pub kind: EarningKind,
+    pub amount: WrappedBalance,
+    pub token_id: Option<AccountId>,
+    pub earned_at: WrappedTimestamp,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Account {
     pub account_id: AccountId,
     pub bio: String,
+    pub created_at: Timestamp,
+    // Public key which workers use to encrypt their deliverables
+    pub encryption_key: Option<Base64VecU8>,
 
+    // Earnings, refunds and deposits, separated from the storage collateral
+    pub wallet_balance: Balance,
+
+    // Only NEAR tasks are counted
     pub total_spent: Balance,
     pub total_earn: Balance,
 
@@ -21,6 +46,13 @@ pub struct Account {
 
     pub current_jobs: UnorderedSet<TaskId>,
     pub completed_jobs: UnorderedSet<TaskId>,
+    pub invited_tasks: UnorderedSet<TaskId>,
+    pub templates: UnorderedSet<TemplateId>,
+    // Proposals submitted by this account as a worker, oldest first.
+    // Withdrawn ones are left as None so the positions don't move
+    pub proposals: Vector<Option<ProposalId>>,
+    // Claimed payments and tips, oldest first
+    pub earnings: Vector<Earning>,
 
     pub pos_point: u32,
     pub neg_point: u32,
@@ -34,6 +66,60 @@ impl Account {
     pub fn add_neg_point(&mut self, point: u32) {
         self.neg_point += point
     }
+
+    pub fn add_earning(
+        &mut self,
+        task_id: TaskId,
+        kind: EarningKind,
+        amount: Balance,
+        token_id: Option<AccountId>,
+    ) {
+        if token_id.is_none() {
+            self.total_earn += amount;
+        }
+        self.earnings.push(&Earning {
+            task_id,
+            kind,
+            amount: amount.into(),
+            token_id,
+            earned_at: env::block_timestamp().into(),
+        });
+    }
+
+    pub fn assert_eligible(&self, requirements: &TaskRequirements) {
+        if let Some(min_pos_point) = requirements.min_pos_point {
+            assert!(
+                self.pos_point >= min_pos_point,
+                "Requires at least {} positive points",
+                min_pos_point
+            );
+        }
+
+        if let Some(max_neg_point_ratio) = requirements.max_neg_point_ratio {
+            assert!(
+                self.neg_point as u64 * 100 <= self.pos_point as u64 * max_neg_point_ratio as u64,
+                "Negative points must be at most {}% of positive points",
+                max_neg_point_ratio
+            );
+        }
+
+        if let Some(min_completed_jobs) = requirements.min_completed_jobs {
+            assert!(
+                self.completed_jobs.len() >= min_completed_jobs,
+                "Requires at least {} completed jobs",
+                min_completed_jobs
+            );
+        }
+
+        if let Some(min_account_age) = requirements.min_account_age {
+            let min_account_age: Duration = min_account_age.into();
+            assert!(
+                self.created_at + min_account_age <= env::block_timestamp(),
+                "Account must be registered for at least {} nanoseconds",
+                min_account_age
+            );
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -41,8 +127,11 @@ impl Account {
 pub struct WrappedAccount {
     pub account_id: String,
     pub bio: String,
+    pub created_at: WrappedTimestamp,
+    pub encryption_key: Option<Base64VecU8>,
     pub locked_balance: Vec<(TaskId, LockedBalance)>,
     pub balance: Option<AccountStorageBalance>,
+    pub wallet_balance: U128,
 
     pub total_spent: U128,
     pub total_earn: U128,
@@ -59,8 +148,11 @@ impl From<Account> for WrappedAccount {
         Self {
             account_id: account.account_id,
             bio: account.bio,
-            locked_balance: account.locked_balance.iter().map(|(k, v)| (k, v)).collect(),
+            created_at: account.created_at.into(),
+            encryption_key: account.encryption_key,
+            locked_balance: account.locked_balance.iter().collect(),
             balance: None,
+            wallet_balance: account.wallet_balance.into(),
 
             total_spent: account.total_spent.into(),
             total_earn: account.total_earn.into(),
@@ -87,6 +179,21 @@ impl Dwork {
         wrapped_account
     }
 
+    // Newest first
+    pub fn earnings_by_worker(
+        &self,
+        account_id: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<Earning> {
+        let earnings = self.internal_get_account(&account_id).earnings;
+
+        calculate_rev_limit(earnings.len(), from_index, limit)
+            .map(|index| earnings.get(index).unwrap())
+            .rev()
+            .collect()
+    }
+
     // Modify method
     pub fn update_bio(&mut self, bio: String) {
         let account_id = env::predecessor_account_id();
@@ -95,72 +202,69 @@ impl Dwork {
         self.internal_set_account(&account_id, account);
     }
 
-    // #[payable]
-    // pub fn deposit(&mut self, account_id: Option<AccountId>) {
-    //     let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
-    //     let mut account = self.internal_get_account(&account_id);
-    //
-    //     assert!(
-    //         env::attached_deposit() >= self.app_config.minimum_deposit
-    //             && env::attached_deposit() <= self.app_config.maximum_deposit,
-    //         "Total amount for each task must be in a range from {} to {}",
-    //         self.app_config.minimum_deposit,
-    //         self.app_config.maximum_deposit
-    //     );
-    //
-    //     account.balance += env::attached_deposit();
-    //     self.internal_set_account(&account_id, account)
-    // }
-    //
-    // pub fn withdraw(&mut self, account_id: Option<AccountId>, amount: Balance) {
-    //     let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
-    //     let account = self.internal_get_account(&account_id);
-    //
-    //     assert!(
-    //         account.balance >= amount,
-    //         "Account doesn't have enough balance"
-    //     );
-    //
-    //     assert!(
-    //         account.pos_point > 50,
-    //         "Account must have positive point higher than {}",
-    //         50
-    //     );
-    //
-    //     if account.neg_point != 0 {
-    //         let rate = account.pos_point / account.neg_point;
-    //         assert!(
-    //             rate > self.app_config.critical_point as u32,
-    //             "Account must have positive point / negative point higher than {}",
-    //             self.app_config.critical_point
-    //         );
-    //     }
-    //
-    //     assert!(
-    //         amount >= self.app_config.minimum_deposit && amount <= self.app_config.maximum_deposit,
-    //         "Amount for each withdraws must be in a range from {} to {}",
-    //         self.app_config.minimum_deposit,
-    //         self.app_config.maximum_deposit
-    //     );
-    //
-    //     Promise::new(account_id.to_string())
-    //         .transfer(amount)
-    //         .then(ext_self::on_transferd(
-    //             account_id,
-    //             amount,
-    //             &env::current_account_id(),
-    //             0,
-    //             DEFAULT_GAS_TO_PAY,
-    //         ));
-    // }
+    // Set none to stop receiving encrypted deliverables
+    pub fn set_encryption_key(&mut self, encryption_key: Option<Base64VecU8>) {
+        let account_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(account_id.clone());
+
+        if let Some(key) = &encryption_key {
+            assert_eq!(key.0.len(), 32, "Encryption key must be 32 bytes");
+        }
+
+        let mut account = self.internal_get_account(&account_id);
+        account.encryption_key = encryption_key;
+        self.internal_set_account(&account_id, account);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    #[payable]
+    pub fn deposit(&mut self, account_id: Option<AccountId>) {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+
+        assert!(
+            env::attached_deposit() >= self.app_config.minimum_deposit
+                && env::attached_deposit() <= self.app_config.maximum_deposit,
+            "Total amount for each deposit must be in a range from {} to {}",
+            self.app_config.minimum_deposit,
+            self.app_config.maximum_deposit
+        );
+
+        self.internal_send(Some(account_id), env::attached_deposit());
+    }
+
+    // Balance is restored by on_transferd if the transfer failed
+    #[payable]
+    pub fn withdraw(&mut self, amount: Option<U128>) -> Promise {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let amount: Balance = amount
+            .map(|item| item.into())
+            .unwrap_or_else(|| self.internal_get_account(&account_id).wallet_balance);
+        assert!(amount > 0, "Nothing to withdraw");
+
+        self.internal_payment(Some(account_id.clone()), amount);
+
+        Promise::new(account_id.clone())
+            .transfer(amount)
+            .then(ext_self::on_transferd(
+                account_id,
+                amount.into(),
+                &env::current_account_id(),
+                0,
+                DEFAULT_GAS_TO_PAY,
+            ))
+    }
 
     pub(crate) fn internal_create_account(&mut self, account_id: &AccountId) -> Account {
         let account = Account {
             account_id: account_id.clone(),
             bio: "A member of dWork".to_string(),
+            created_at: env::block_timestamp(),
+            encryption_key: None,
             total_earn: 0,
             total_spent: 0,
-            // balance: env::attached_deposit(),
+            wallet_balance: 0,
             locked_balance: UnorderedMap::new(StorageKey::UserLockedBalance {
                 account_id: account_id.clone(),
             }),
@@ -170,6 +274,18 @@ impl Dwork {
             current_jobs: UnorderedSet::new(StorageKey::UserCurrentTasks {
                 account_id: account_id.clone(),
             }),
+            invited_tasks: UnorderedSet::new(StorageKey::UserInvitedTasks {
+                account_id: account_id.clone(),
+            }),
+            templates: UnorderedSet::new(StorageKey::UserTemplates {
+                account_id: account_id.clone(),
+            }),
+            proposals: Vector::new(StorageKey::UserProposals {
+                account_id: account_id.clone(),
+            }),
+            earnings: Vector::new(StorageKey::UserEarnings {
+                account_id: account_id.clone(),
+            }),
             pos_point: 0,
             neg_point: 0,
         };
@@ -195,4 +311,29 @@ impl Dwork {
     pub(crate) fn internal_set_account(&mut self, account_id: &AccountId, account: Account) {
         self.accounts.insert(account_id, &account);
     }
+
+    pub(crate) fn internal_available_balance(&self, account_id: &AccountId) -> Balance {
+        self.internal_get_account(account_id).wallet_balance
+    }
+
+    // Pay from the wallet balance
+    pub(crate) fn internal_payment(&mut self, account_id: Option<AccountId>, amount: Balance) {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let mut account = self.internal_get_account(&account_id);
+        assert!(
+            amount <= account.wallet_balance,
+            "Wallet balance not available to pay {} for internal request",
+            amount
+        );
+        account.wallet_balance -= amount;
+        self.internal_set_account(&account_id, account);
+    }
+
+    // Send to the wallet balance
+    pub(crate) fn internal_send(&mut self, account_id: Option<AccountId>, amount: Balance) {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let mut account = self.internal_get_account(&account_id);
+        account.wallet_balance += amount;
+        self.internal_set_account(&account_id, account);
+    }
 }
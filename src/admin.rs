@@ -25,6 +25,7 @@ pub struct AppConfig {
 
     pub report_interval: Timestamp,
     pub validate_report_interval: Timestamp,
+    pub review_interval: Duration,
 
     pub minimum_reward_per_task: Balance,
     pub maximum_reward_per_task: Balance,
@@ -33,6 +34,11 @@ pub struct AppConfig {
     pub maximum_proposals_at_one_time: u16,
     pub maximum_requests_active_per_user: u16,
     pub maximum_title_length: u16,
+    pub maximum_milestones_per_task: u8,
+    pub maximum_tasks_per_batch: u16,
+    pub maximum_revision_rounds: u8,
+    pub maximum_deliverables_per_proposal: u8,
+    pub maximum_message_length: u16,
     
     pub minimum_deposit: Balance,
     pub maximum_deposit: Balance,
@@ -47,6 +53,9 @@ pub struct AppConfig {
     
     pub claim_point_bonus: u32, // may be a near bonus was given by requester to pay for who call
                                 // first claim / complete task
+    pub finalize_bounty: Balance, // paid from task remainder to who finalize an expired NEAR task
+    pub fee_rate_bps: u16, // platform fee on top of the task buget
+    pub treasury_id: Option<AccountId>,
     pub critical_point: u16,
 }
 
@@ -58,6 +67,7 @@ impl Default for AppConfig {
             submit_bond: 10_000_000_000_000_000_000_000,
             report_interval: 172_800_000_000_000, // 2 days
             validate_report_interval: 259_200_000_000_000, // 3 days
+            review_interval: 604_800_000_000_000, // 7 days
             minimum_reward_per_task: 10_000_000_000_000_000_000_000,
             maximum_reward_per_task: 100_000_000_000_000_000_000_000_000,
             maximum_description_length: 10000,
@@ -65,11 +75,19 @@ impl Default for AppConfig {
             maximum_proposals_at_one_time: 200,
             maximum_requests_active_per_user: 10,
             maximum_title_length: 100,
+            maximum_milestones_per_task: 10,
+            maximum_tasks_per_batch: 20,
+            maximum_revision_rounds: 3,
+            maximum_deliverables_per_proposal: 10,
+            maximum_message_length: 2000,
 
             minimum_deposit: 100_000_000_000_000_000_000_000, // 0.1 N
             maximum_deposit: 500_000_000_000_000_000_000_000_000, // 500 N
 
             claim_point_bonus: 10,
+            finalize_bounty: 10_000_000_000_000_000_000_000, // 0.01 N
+            fee_rate_bps: 1000, // 10%
+            treasury_id: None,
             critical_point: 85,
             
             big_plus: 15,
@@ -96,7 +114,14 @@ impl Dwork {
         submit_bond: Option<Balance>,
         min_reward: Option<Balance>,
         max_reward: Option<Balance>,
+        finalize_bounty: Option<Balance>,
+        review_interval: Option<WrappedDuration>,
     ) {
+        assert!(
+            self.is_admin(env::predecessor_account_id()),
+            "Just admin can change config"
+        );
+
         if let Some(register_bond) = register_bond {
             self.app_config.register_bond = register_bond;
         }
@@ -112,6 +137,14 @@ impl Dwork {
         if let Some(max_reward) = max_reward {
             self.app_config.maximum_reward_per_task = max_reward;
         }
+
+        if let Some(finalize_bounty) = finalize_bounty {
+            self.app_config.finalize_bounty = finalize_bounty;
+        }
+
+        if let Some(review_interval) = review_interval {
+            self.app_config.review_interval = review_interval.into();
+        }
     }
 
     pub fn add_admin(&mut self, account_id: AccountId) -> bool {
@@ -142,6 +175,23 @@ impl Dwork {
         self.admins.remove(&account_id)
     }
 
+    pub fn add_token(&mut self, token_id: AccountId) -> bool {
+        assert!(
+            self.is_admin(env::predecessor_account_id()),
+            "Just admin can whitelist tokens"
+        );
+        self.whitelisted_tokens.insert(&token_id)
+    }
+
+    // Tasks which are already paid with this token are not affected
+    pub fn remove_token(&mut self, token_id: AccountId) -> bool {
+        assert!(
+            self.is_admin(env::predecessor_account_id()),
+            "Just admin can remove tokens"
+        );
+        self.whitelisted_tokens.remove(&token_id)
+    }
+
     /// Change state of contract, Only can be called by owner.
     #[payable]
     pub fn change_state(&mut self, state: RunningState) {
@@ -176,17 +226,22 @@ impl Dwork {
             "Can't approved this report"
         );
 
-        let task = self.internal_get_task(&report.task_id);
+        let mut task = self.internal_get_task(&report.task_id);
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
+        let amount = self.internal_milestone_amount(&task, report.milestone);
         let (proposal_id, mut proposal) =
             self.internal_get_proposal(report.task_id.clone(), report.account_id.clone());
 
-        match proposal.status {
+        match proposal.status_of(report.milestone) {
             ProposalStatus::Rejected {
                 reason: _,
                 reject_at: _,
-                report_id,
+                report_id: proposal_report_id,
             } => {
-                assert!(report_id.is_none(), "Invalid report");
+                assert!(
+                    proposal_report_id.as_ref() == Some(&report_id),
+                    "Invalid report"
+                );
             }
             _ => panic!("Invalid report"),
         }
@@ -196,8 +251,23 @@ impl Dwork {
         self.reports.insert(&report_id, &report);
 
         // Update Proposal Status
-        proposal.status = ProposalStatus::ApprovedByAdmin{account_id: env::predecessor_account_id()};
-        self.proposals.insert(&proposal_id, &proposal);
+        proposal.set_status(
+            report.milestone,
+            ProposalStatus::ApprovedByAdmin {
+                account_id: env::predecessor_account_id(),
+            },
+        );
+        // Worker takes a seat when the first milestone is approved
+        if report.milestone.is_some() && proposal.status == ProposalStatus::Pending {
+            assert!(
+                task.proposal_count(ProposalStatusKind::Approved) < task.max_participants.into(),
+                "All {} seats are already taken",
+                task.max_participants
+            );
+            proposal.status = ProposalStatus::Approved;
+        }
+        self.internal_set_proposal(&mut task, &proposal_id, &proposal);
+        self.task_recores.insert(&report.task_id, &task);
 
         /* Update Worker Locked balance
          * - Add Locked Balance for this worker
@@ -213,21 +283,36 @@ impl Dwork {
             None => env::block_timestamp(),
         };
         let new_locked_balance = LockedBalance {
-            amount: task.price,
+            amount,
             release_at,
+            token_id: task.token_id.clone(),
             // Must be the last rejection deadline report + 3 days
         };
         
         worker.add_pos_point(self.app_config.sml_plus as u32);
-        worker.locked_balance.insert(&report.task_id, &new_locked_balance);
+        worker.locked_balance.insert(
+            &self.internal_gen_milestone_key(&report.task_id, report.milestone),
+            &new_locked_balance,
+        );
         self.internal_set_account(&report.account_id, worker);
 
         // BIG minus for wrong rejection
         owner.add_neg_point(self.app_config.big_minus as u32);
         self.internal_set_account(&task.owner, owner);
         
-        let mut num_approvals = 0;
-        for proposal_id in task.proposals.iter() {
+        // Seats are not limited per milestone
+        if !task.milestones.is_empty() {
+            return;
+        }
+
+        // Workers approved by an admin keep their seat, the latest approvals of the owner give theirs up
+        let mut num_approvals = task.proposal_count(ProposalStatusKind::ApprovedByAdmin);
+        assert!(
+            num_approvals <= task.max_participants.into(),
+            "All {} seats are already taken",
+            task.max_participants
+        );
+        for proposal_id in task.proposals.clone().iter() {
             let mut proposal = self
                 .proposals
                 .get(proposal_id)
@@ -235,15 +320,19 @@ impl Dwork {
 
             match proposal.status {
                 ProposalStatus::Approved => {
-                    if num_approvals >= task.max_participants {
+                    if num_approvals >= task.max_participants.into() {
                         proposal.status = ProposalStatus::Rejected {
                             reason: "late".to_string(),
                             reject_at: env::block_timestamp(),
                             report_id: None,
                         };
+                        self.internal_set_proposal(&mut task, proposal_id, &proposal);
                         // Remove locked balance
                         let mut worker = self.internal_get_account(&proposal.account_id);
-                        worker.locked_balance.remove(&report.task_id);
+                        worker
+                            .locked_balance
+                            .remove(&self.internal_gen_milestone_key(&report.task_id, None));
+                        self.internal_set_account(&proposal.account_id, worker);
                     } else {
                         num_approvals += 1;
                     }
@@ -252,6 +341,7 @@ impl Dwork {
                 _ => {}
             }
         }
+        self.task_recores.insert(&report.task_id, &task);
     }
 
     pub fn reject_report(&mut self, report_id: ReportId) {
@@ -262,17 +352,21 @@ impl Dwork {
             "Cann't reject this report"
         );
 
+        let mut task = self.internal_get_task(&report.task_id);
         let mut worker = self.internal_get_account(&report.account_id);
         let (proposal_id, mut proposal) =
             self.internal_get_proposal(report.task_id.clone(), report.account_id.clone());
         
-        match proposal.status {
+        match proposal.status_of(report.milestone) {
             ProposalStatus::Rejected {
                 reason: _,
                 reject_at: _,
-                report_id,
+                report_id: proposal_report_id,
             } => {
-                assert!(report_id.is_none(), "Invalid report");
+                assert!(
+                    proposal_report_id.as_ref() == Some(&report_id),
+                    "Invalid report"
+                );
             }
             _ => panic!("Invalid report"),
         }
@@ -283,7 +377,13 @@ impl Dwork {
         report.status = ReportStatus::Rejected;
         self.reports.insert(&report_id, &report);
 
-        proposal.status = ProposalStatus::RejectedByAdmin {account_id: env::predecessor_account_id()};
-        self.proposals.insert(&proposal_id, &proposal);
+        proposal.set_status(
+            report.milestone,
+            ProposalStatus::RejectedByAdmin {
+                account_id: env::predecessor_account_id(),
+            },
+        );
+        self.internal_set_proposal(&mut task, &proposal_id, &proposal);
+        self.task_recores.insert(&report.task_id, &task);
     }
 }
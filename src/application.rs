@@ -0,0 +1,111 @@
+use crate::*;
+
+pub type ApplicationId = String;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum HiringStatus {
+    Open,
+    Closed,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ApplicationStatus {
+    Pending,
+    Hired,
+    Declined,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Application {
+    pub account_id: AccountId,
+    pub cover_letter: String,
+    pub estimated_hours: u32,
+    pub applied_at: Timestamp,
+    pub bond: Balance,
+    pub status: ApplicationStatus,
+}
+
+#[near_bindgen]
+impl Dwork {
+    pub fn applications_by_task(
+        &self,
+        task_id: TaskId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<Application> {
+        let application_ids = self.internal_get_task(&task_id).applications;
+
+        calculate_rev_limit(application_ids.len() as u64, from_index, limit)
+            .map(|index| {
+                let key = application_ids.get(index as usize).unwrap();
+                self.applications.get(key).expect("Application not found")
+            })
+            .rev()
+            .collect()
+    }
+}
+
+impl Dwork {
+    pub(crate) fn internal_get_application(
+        &self,
+        task_id: TaskId,
+        worker_id: AccountId,
+    ) -> Option<(ApplicationId, Application)> {
+        let application_id = self.internal_gen_proposal_id(task_id, worker_id);
+        self.applications
+            .get(&application_id)
+            .map(|application| (application_id, application))
+    }
+
+    // Refund bond of the applicants who were not hired
+    pub(crate) fn internal_close_hiring(&mut self, task_id: &TaskId, task: &mut Task) {
+        assert!(
+            task.hiring == Some(HiringStatus::Open),
+            "Task is not hiring"
+        );
+
+        for application_id in task.applications.iter() {
+            let mut application = self
+                .applications
+                .get(application_id)
+                .expect("Application not found");
+
+            if application.status == ApplicationStatus::Pending {
+                self.internal_send(Some(application.account_id.clone()), application.bond);
+
+                let mut worker = self.internal_get_account(&application.account_id);
+                worker.current_jobs.remove(task_id);
+                self.internal_set_account(&application.account_id, worker);
+
+                application.bond = 0;
+                application.status = ApplicationStatus::Declined;
+                self.applications.insert(application_id, &application);
+            }
+        }
+
+        task.hiring = Some(HiringStatus::Closed);
+    }
+
+    // Bonds of hired workers who haven't submitted anything yet
+    pub(crate) fn internal_refund_application_bonds(&mut self, task_id: &TaskId, task: &Task) {
+        for application_id in task.applications.iter() {
+            let mut application = self
+                .applications
+                .get(application_id)
+                .expect("Application not found");
+
+            if application.bond > 0 {
+                self.internal_send(Some(application.account_id.clone()), application.bond);
+                application.bond = 0;
+                self.applications.insert(application_id, &application);
+
+                let mut worker = self.internal_get_account(&application.account_id);
+                worker.current_jobs.remove(task_id);
+                self.internal_set_account(&application.account_id, worker);
+            }
+        }
+    }
+}
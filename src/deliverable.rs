@@ -0,0 +1,121 @@
+use crate::*;
+
+const MAXIMUM_URL_LENGTH: usize = 1000;
+const MINIMUM_CID_LENGTH: usize = 46;
+const MAXIMUM_CID_LENGTH: usize = 100;
+const SHA1_HEX_LENGTH: usize = 40;
+const SHA256_HEX_LENGTH: usize = 64;
+const MAXIMUM_CIPHERTEXT_LENGTH: usize = 4096;
+const NONCE_LENGTH: usize = 24;
+const PUBLIC_KEY_LENGTH: usize = 32;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Deliverable {
+    // Link to a github repo, figma design files, etc
+    Url { url: String },
+    Ipfs { cid: String },
+    GitCommit { repo: String, sha: String },
+    // Sha256 of a file shared off-chain
+    ContentHash { sha256: String },
+    // Box encrypted for the owner's encryption key, stored as is
+    Encrypted {
+        ciphertext: Base64VecU8,
+        nonce: Base64VecU8,
+        sender_public_key: Base64VecU8,
+        recipient_public_key: Base64VecU8,
+    },
+}
+
+fn assert_url(url: &str) {
+    assert!(
+        url.starts_with("https://") || url.starts_with("http://"),
+        "Url must start with http:// or https://"
+    );
+    assert!(url.len() <= MAXIMUM_URL_LENGTH, "Url too long");
+    assert!(
+        !url.chars().any(|c| c.is_whitespace()),
+        "Url must not contain whitespaces"
+    );
+}
+
+fn assert_hex(value: &str, lengths: &[usize], name: &str) {
+    assert!(
+        lengths.contains(&value.len()) && value.chars().all(|c| c.is_ascii_hexdigit()),
+        "Invalid {}",
+        name
+    );
+}
+
+impl Deliverable {
+    pub fn assert_valid(&self) {
+        match self {
+            Deliverable::Url { url } => assert_url(url),
+            Deliverable::Ipfs { cid } => {
+                // CIDv0 starts with "Qm", CIDv1 is usually base32 and starts with "b"
+                assert!(
+                    (MINIMUM_CID_LENGTH..=MAXIMUM_CID_LENGTH).contains(&cid.len())
+                        && cid.chars().all(|c| c.is_ascii_alphanumeric()),
+                    "Invalid IPFS cid"
+                );
+            }
+            Deliverable::GitCommit { repo, sha } => {
+                assert_url(repo);
+                assert_hex(sha, &[SHA1_HEX_LENGTH, SHA256_HEX_LENGTH], "commit sha");
+            }
+            Deliverable::ContentHash { sha256 } => {
+                assert_hex(sha256, &[SHA256_HEX_LENGTH], "content hash");
+            }
+            Deliverable::Encrypted {
+                ciphertext,
+                nonce,
+                sender_public_key,
+                recipient_public_key,
+            } => {
+                assert!(
+                    !ciphertext.0.is_empty() && ciphertext.0.len() <= MAXIMUM_CIPHERTEXT_LENGTH,
+                    "Ciphertext must be from 1 to {} bytes",
+                    MAXIMUM_CIPHERTEXT_LENGTH
+                );
+                assert_eq!(nonce.0.len(), NONCE_LENGTH, "Invalid nonce");
+                assert_eq!(sender_public_key.0.len(), PUBLIC_KEY_LENGTH, "Invalid sender key");
+                assert_eq!(recipient_public_key.0.len(), PUBLIC_KEY_LENGTH, "Invalid recipient key");
+            }
+        }
+    }
+}
+
+impl Dwork {
+    // Encrypted deliverables must be sent to the recipient's registered key, not allowed without recipient
+    pub(crate) fn internal_assert_deliverables(
+        &self,
+        deliverables: &[Deliverable],
+        recipient: Option<&AccountId>,
+    ) {
+        assert!(!deliverables.is_empty(), "Submit at least one deliverable");
+        assert!(
+            deliverables.len() <= self.app_config.maximum_deliverables_per_proposal as usize,
+            "Maximum {} deliverables",
+            self.app_config.maximum_deliverables_per_proposal
+        );
+        for deliverable in deliverables {
+            deliverable.assert_valid();
+
+            if let Deliverable::Encrypted {
+                recipient_public_key,
+                ..
+            } = deliverable
+            {
+                let recipient = recipient.expect("Encrypted deliverables are not allowed here");
+                let encryption_key = self
+                    .internal_get_account(recipient)
+                    .encryption_key
+                    .expect("Task owner has no encryption key");
+                assert!(
+                    *recipient_public_key == encryption_key,
+                    "Encrypted for an outdated key of the task owner"
+                );
+            }
+        }
+    }
+}
@@ -1,4 +1,98 @@
-// use super::*;
+use crate::*;
+use near_sdk::json_types::U128;
+use near_sdk::{ext_contract, PromiseResult};
+
+pub const GAS_FOR_FT_TRANSFER: Gas = 10_000_000_000_000;
+
+#[ext_contract(ext_ft)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[ext_contract(ext_self)]
+pub trait ExtDupwork {
+    fn on_ft_transfer(&mut self, account_id: AccountId, token_id: AccountId, amount: U128) -> bool;
+    fn on_transferd(&mut self, account_id: AccountId, amount: U128) -> bool;
+    fn on_fees_withdrawn(&mut self, token_id: Option<AccountId>, amount: U128) -> bool;
+}
+
+#[near_bindgen]
+impl Dwork {
+    // Keep the tokens in the token balance of the receiver when the transfer failed
+    pub fn on_ft_transfer(&mut self, account_id: AccountId, token_id: AccountId, amount: U128) -> bool {
+        assert!(
+            env::predecessor_account_id() == env::current_account_id(),
+            "Callback is not called from the contract itself",
+        );
+
+        assert!(
+            env::promise_results_count() == 1,
+            "Function called not as a callback",
+        );
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                env::log(
+                    format!(
+                        "Failed to transfer {} {} to {}, kept in token balance",
+                        amount.0, token_id, account_id
+                    )
+                    .as_bytes(),
+                );
+                self.internal_token_send(&account_id, &token_id, amount.into());
+                false
+            }
+        }
+    }
+
+    // Wallet balance is withdrawn before the transfer, restore it when the transfer failed
+    pub fn on_transferd(&mut self, account_id: AccountId, amount: U128) -> bool {
+        assert!(
+            env::predecessor_account_id() == env::current_account_id(),
+            "Callback is not called from the contract itself",
+        );
+
+        assert!(
+            env::promise_results_count() == 1,
+            "Function called not as a callback",
+        );
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                env::log(
+                    format!("Failed to withdraw {} to {}, restored", amount.0, account_id).as_bytes(),
+                );
+                self.internal_send(Some(account_id), amount.into());
+                false
+            }
+        }
+    }
+
+    // Collected fees are removed before the transfer, restore them when the transfer failed
+    pub fn on_fees_withdrawn(&mut self, token_id: Option<AccountId>, amount: U128) -> bool {
+        assert!(
+            env::predecessor_account_id() == env::current_account_id(),
+            "Callback is not called from the contract itself",
+        );
+
+        assert!(
+            env::promise_results_count() == 1,
+            "Function called not as a callback",
+        );
+
+        match env::promise_result(0) {
+            PromiseResult::Successful(_) => true,
+            _ => {
+                env::log(format!("Failed to withdraw {} fees, restored", amount.0).as_bytes());
+                self.internal_collect_fee(&token_id, amount.into());
+                false
+            }
+        }
+    }
+}
+
 
 // #[ext_contract(ext_self)]
 // pub trait ExtDupwork {
@@ -107,25 +201,4 @@
     //         _ => false,
     //     }
     // }
-    // pub fn on_transferd(&mut self, account_id: AccountId, amount: Balance) -> bool {
-    //     assert!(
-    //         env::predecessor_account_id() == env::current_account_id(),
-    //         "Callback is not called from the contract itself",
-    //     );
-    //
-    //     assert!(
-    //         env::promise_results_count() == 1,
-    //         "Function called not as a callback",
-    //     );
-    //
-    //     match env::promise_result(0) {
-    //         PromiseResult::Successful(_) => {
-    //             let mut account = self.internal_get_account(&account_id);
-    //             account.balance -= amount;
-    //             self.internal_set_account(&account_id, account);
-    //             true
-    //         }
-    //         _ => false,
-    //     }
-    // }
 // }
@@ -1,6 +1,6 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet};
-use near_sdk::json_types::{WrappedBalance, WrappedDuration, WrappedTimestamp};
+use near_sdk::collections::{LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::json_types::{Base64VecU8, WrappedBalance, WrappedDuration, WrappedTimestamp};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
     env, near_bindgen, setup_alloc, AccountId, Balance, BorshStorageKey, Duration, Gas,
@@ -11,33 +11,45 @@ pub const DEFAULT_GAS_TO_PAY: Gas = 20_000_000_000_000;
 
 pub use crate::admin::*;
 pub use crate::categories::*;
+pub use crate::deliverable::*;
+pub use crate::message::*;
 pub use crate::ext::*;
 
 pub use crate::account::*;
+pub use crate::application::*;
 pub use crate::proposal::*;
 pub use crate::report::*;
 pub use crate::task::*;
-
-pub use crate::requester_action::*;
-pub use crate::worker_action::*;
+pub use crate::template::*;
+pub use crate::token::*;
+pub use crate::treasury::*;
 
 pub use crate::storage::*;
 pub use crate::utils::*;
 
 mod admin;
 mod categories;
+mod deliverable;
+mod message;
 mod ext;
 mod proposal;
 
 mod account;
+mod application;
 mod report;
 mod requester_action;
 mod task;
+mod template;
+mod token;
+mod treasury;
 mod worker_action;
 
 mod storage;
 mod utils;
 
+#[cfg(test)]
+mod tests;
+
 setup_alloc!();
 
 #[near_bindgen]
@@ -51,13 +63,25 @@ pub struct Dwork {
     pub app_config: AppConfig,
 
     pub task_recores: UnorderedMap<TaskId, Task>,
+    pub next_task_nonce: u64,
     pub proposals: LookupMap<ProposalId, Proposal>,
+    pub applications: LookupMap<ApplicationId, Application>,
     pub reports: UnorderedMap<ReportId, Report>,
+    pub messages: LookupMap<ProposalId, Vector<Message>>,
+
+    pub whitelisted_tokens: UnorderedSet<AccountId>,
+    pub token_balances: LookupMap<String, Balance>,
+
+    pub treasury_balance: Balance,
+    pub treasury_token_balances: UnorderedMap<AccountId, Balance>,
 
     pub categories: UnorderedMap<CategoryId, Category>,
+
+    pub task_templates: UnorderedMap<TemplateId, TaskTemplate>,
+    pub next_template_id: TemplateId,
 }
 
-//NOTE: We do not keep the submitted bond as a locked balance.
+//NOTE: Submitted bonds are kept in the proposal and settled when the task is closed.
 #[near_bindgen]
 impl Dwork {
     #[init]
@@ -73,10 +97,22 @@ impl Dwork {
             app_config: AppConfig::default(),
 
             task_recores: UnorderedMap::new(StorageKey::TaskRecores),
+            next_task_nonce: 0,
             proposals: LookupMap::new(StorageKey::Proposals),
+            applications: LookupMap::new(StorageKey::Applications),
             reports: UnorderedMap::new(StorageKey::Reports),
+            messages: LookupMap::new(StorageKey::Messages),
+
+            whitelisted_tokens: UnorderedSet::new(StorageKey::WhitelistedTokens),
+            token_balances: LookupMap::new(StorageKey::TokenBalances),
+
+            treasury_balance: 0,
+            treasury_token_balances: UnorderedMap::new(StorageKey::TreasuryTokenBalances),
 
             categories: UnorderedMap::new(StorageKey::Categories),
+
+            task_templates: UnorderedMap::new(StorageKey::TaskTemplates),
+            next_template_id: 0,
         }
     }
 
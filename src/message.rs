@@ -0,0 +1,75 @@
+use crate::*;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Message {
+    pub author: AccountId,
+    pub body: String,
+    pub sent_at: Timestamp,
+}
+
+#[near_bindgen]
+impl Dwork {
+    // Owner and worker can always post, admins only when the proposal was reported
+    pub fn post_message(&mut self, task_id: TaskId, worker_id: AccountId, body: String) {
+        let author = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(author.clone());
+
+        let task = self.internal_get_task(&task_id);
+        let (proposal_id, proposal) = self.internal_get_proposal(task_id, worker_id.clone());
+
+        let is_reported = proposal.all_statuses().any(|status| {
+            matches!(
+                status,
+                ProposalStatus::Rejected {
+                    report_id: Some(_),
+                    ..
+                }
+            )
+        });
+        assert!(
+            author == task.owner
+                || author == worker_id
+                || (is_reported && self.is_admin(author.clone())),
+            "Only owner, worker or admins handling a report can post"
+        );
+        assert!(!body.is_empty(), "Message is empty");
+        assert!(
+            body.len() <= self.app_config.maximum_message_length.into(),
+            "Message too long"
+        );
+
+        let mut messages = self.messages.get(&proposal_id).unwrap_or_else(|| {
+            Vector::new(StorageKey::ProposalMessages {
+                proposal_id: proposal_id.clone(),
+            })
+        });
+        messages.push(&Message {
+            author,
+            body,
+            sent_at: env::block_timestamp(),
+        });
+        self.messages.insert(&proposal_id, &messages);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Newest first
+    pub fn messages_by_proposal(
+        &self,
+        task_id: TaskId,
+        worker_id: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<Message> {
+        let proposal_id = self.internal_gen_proposal_id(task_id, worker_id);
+
+        match self.messages.get(&proposal_id) {
+            Some(messages) => calculate_rev_limit(messages.len(), from_index, limit)
+                .map(|index| messages.get(index).unwrap())
+                .rev()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
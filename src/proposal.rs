@@ -2,9 +2,11 @@ use crate::*;
 
 pub type ProposalId = String;
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub enum ProposalStatus {
+    // Only a hash of the work was submitted, waiting for the reveal
+    Committed,
     Pending,
     Approved,
     Rejected {
@@ -12,15 +14,263 @@ pub enum ProposalStatus {
         reject_at: Timestamp,
         report_id: Option<ReportId>,
     },
+    // Owner asked the worker to resubmit, can't be reported
+    ChangesRequested { note: String },
     ApprovedByAdmin {account_id: AccountId},
-    RejectedByAdmin {account_id: AccountId}
+    RejectedByAdmin {account_id: AccountId},
+    Cancelled,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WorkerProposal {
+    pub title: String,
+    pub price: WrappedBalance,
+    pub token_id: Option<AccountId>,
+    pub task_status: TaskStatus,
+    // Approved amount which can't be claimed yet
+    pub locked: WrappedBalance,
+    pub claimable: WrappedBalance,
+    pub proposal: Proposal,
+}
+
+// Status without its details, used to count and filter proposals
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord, Hash,
+    Clone, Copy,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalStatusKind {
+    Committed,
+    Pending,
+    Approved,
+    Rejected,
+    ChangesRequested,
+    ApprovedByAdmin,
+    RejectedByAdmin,
+    Cancelled,
+}
+
+impl ProposalStatus {
+    pub fn kind(&self) -> ProposalStatusKind {
+        match self {
+            ProposalStatus::Committed => ProposalStatusKind::Committed,
+            ProposalStatus::Pending => ProposalStatusKind::Pending,
+            ProposalStatus::Approved => ProposalStatusKind::Approved,
+            ProposalStatus::Rejected { .. } => ProposalStatusKind::Rejected,
+            ProposalStatus::ChangesRequested { .. } => ProposalStatusKind::ChangesRequested,
+            ProposalStatus::ApprovedByAdmin { .. } => ProposalStatusKind::ApprovedByAdmin,
+            ProposalStatus::RejectedByAdmin { .. } => ProposalStatusKind::RejectedByAdmin,
+            ProposalStatus::Cancelled => ProposalStatusKind::Cancelled,
+        }
+    }
+
+    // Waiting for owner's decision or for worker's resubmission
+    pub fn is_in_review(&self) -> bool {
+        matches!(
+            self,
+            ProposalStatus::Pending | ProposalStatus::ChangesRequested { note: _ }
+        )
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum BondStatus {
+    Locked,
+    Refunded,
+    // Report of this proposal was rejected by an admin
+    Forfeited,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SubmitBond {
+    pub amount: Balance,
+    pub status: BondStatus,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProofRevision {
+    pub submit_time: Timestamp,
+    pub proof_of_work: Vec<Deliverable>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MilestoneSubmission {
+    pub submit_time: Timestamp,
+    pub proof_of_work: Vec<Deliverable>,
+    pub status: ProposalStatus,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Proposal {
+    pub task_id: TaskId,
     pub account_id: AccountId,
     pub submit_time: Timestamp,
-    pub proof_of_work: String, //prefer an url like github repo or figma design files, etc
+    pub proof_of_work: Vec<Deliverable>,
     pub status: ProposalStatus,
+    pub bond: SubmitBond,
+    // Hash committed before the reveal of a commit-reveal task
+    pub commitment: Option<Base64VecU8>,
+    // Submissions of a milestone task, ordered by milestone
+    pub milestones: Vec<MilestoneSubmission>,
+    // Previous proofs, oldest first
+    pub revisions: Vec<ProofRevision>,
+    // Worker already got the point bonus for a tip of this task
+    pub tipped: bool,
+    // Position in the proposals of the worker
+    pub worker_index: u64,
+}
+
+impl Proposal {
+    pub fn status_of(&self, milestone: Option<u8>) -> &ProposalStatus {
+        match milestone {
+            Some(index) => {
+                &self
+                    .milestones
+                    .get(index as usize)
+                    .expect("Milestone submission not found")
+                    .status
+            }
+            None => &self.status,
+        }
+    }
+
+    pub fn set_status(&mut self, milestone: Option<u8>, status: ProposalStatus) {
+        match milestone {
+            Some(index) => {
+                self.milestones
+                    .get_mut(index as usize)
+                    .expect("Milestone submission not found")
+                    .status = status
+            }
+            None => self.status = status,
+        }
+    }
+
+    // Statuses of the proposal and of all its milestones
+    pub fn all_statuses(&self) -> impl Iterator<Item = &ProposalStatus> {
+        std::iter::once(&self.status).chain(self.milestones.iter().map(|item| &item.status))
+    }
+}
+
+#[near_bindgen]
+impl Dwork {
+    // Ordered by submission, oldest first
+    pub fn proposals_by_task(
+        &self,
+        task_id: TaskId,
+        status_filter: Option<ProposalStatusKind>,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<Proposal> {
+        self.internal_get_task(&task_id)
+            .proposals
+            .iter()
+            .map(|proposal_id| self.proposals.get(proposal_id).expect("Proposal not found"))
+            .filter(|proposal| status_filter.is_none_or(|kind| proposal.status.kind() == kind))
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    // Ordered by submission, oldest first. Paging is over all submissions, so a page
+    // can hold less than limit proposals when some were withdrawn or filtered out
+    pub fn proposals_by_worker(
+        &self,
+        account_id: AccountId,
+        status_filter: Option<ProposalStatusKind>,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<WorkerProposal> {
+        let worker = self.internal_get_account(&account_id);
+        let now = env::block_timestamp();
+
+        (from_index..std::cmp::min(from_index.saturating_add(limit), worker.proposals.len()))
+            .filter_map(|index| worker.proposals.get(index).unwrap())
+            .map(|proposal_id| self.proposals.get(&proposal_id).expect("Proposal not found"))
+            .filter(|proposal| status_filter.is_none_or(|kind| proposal.status.kind() == kind))
+            .map(|proposal| {
+                let task = self.internal_get_task(&proposal.task_id);
+
+                // Locked balances are kept per milestone
+                let keys: Vec<String> = if task.milestones.is_empty() {
+                    vec![self.internal_gen_milestone_key(&proposal.task_id, None)]
+                } else {
+                    (0..task.milestones.len() as u8)
+                        .map(|index| self.internal_gen_milestone_key(&proposal.task_id, Some(index)))
+                        .collect()
+                };
+                let (mut locked, mut claimable): (Balance, Balance) = (0, 0);
+                for LockedBalance {
+                    amount, release_at, ..
+                } in
+                    keys.iter().filter_map(|key| worker.locked_balance.get(key))
+                {
+                    if release_at < now {
+                        claimable += amount;
+                    } else {
+                        locked += amount;
+                    }
+                }
+
+                WorkerProposal {
+                    title: task.title,
+                    price: task.price.into(),
+                    token_id: task.token_id,
+                    task_status: task.status,
+                    locked: locked.into(),
+                    claimable: claimable.into(),
+                    proposal,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Dwork {
+    // Stores the proposal and counts its new status, the task must be saved by the caller
+    pub(crate) fn internal_set_proposal(
+        &mut self,
+        task: &mut Task,
+        proposal_id: &ProposalId,
+        proposal: &Proposal,
+    ) {
+        if let Some(previous) = self.proposals.get(proposal_id) {
+            task.remove_proposal_count(previous.status.kind());
+        }
+        task.add_proposal_count(proposal.status.kind());
+        self.proposals.insert(proposal_id, proposal);
+    }
+
+    // Refund bonds when the task is closed, except for proposals whose report was rejected
+    pub(crate) fn internal_settle_bonds(&mut self, task: &Task) {
+        for proposal_id in task.proposals.iter() {
+            let mut proposal = self
+                .proposals
+                .get(proposal_id)
+                .expect("Proposal not found");
+
+            if proposal.bond.status != BondStatus::Locked {
+                continue;
+            }
+
+            // Unrevealed commitments and reveals which don't match them are forfeited as well
+            if proposal.all_statuses().any(|status| match status {
+                ProposalStatus::RejectedByAdmin { account_id: _ } | ProposalStatus::Committed => true,
+                ProposalStatus::Rejected { reason, .. } => reason == "mismatch",
+                _ => false,
+            }) {
+                proposal.bond.status = BondStatus::Forfeited;
+            } else {
+                self.internal_send(Some(proposal.account_id.clone()), proposal.bond.amount);
+                proposal.bond.status = BondStatus::Refunded;
+            }
+            self.proposals.insert(proposal_id, &proposal);
+        }
+    }
 }
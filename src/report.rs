@@ -16,7 +16,8 @@ pub struct Report {
     pub report_id: ReportId,
     pub account_id: AccountId,
     pub task_id: TaskId,
-    pub report: String, //prefer an url like github repo or figma design files, etc
+    pub milestone: Option<u8>,
+    pub report: Vec<Deliverable>,
     pub status: ReportStatus,
 }
 
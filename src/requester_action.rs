@@ -1,178 +1,475 @@
 use crate::*;
+use std::collections::BTreeMap;
 
 #[near_bindgen]
 impl Dwork {
-    // #[payable]
-    pub fn new_task(
-        &mut self,
-        title: String,
-        description: String,
-        price: WrappedBalance,
-        max_participants: u16,
-        duration: WrappedDuration,
-        category_id: CategoryId,
-    ) {
+    // Attached deposit must cover the budget, fee and storage, the excess stays in the owner's wallet.
+    // Without deposit the task is paid from the owner's wallet
+    #[payable]
+    pub fn new_task(&mut self, task: TaskInput) -> TaskId {
         let owner_id = env::predecessor_account_id();
-        let mut owner = self.internal_get_account(&owner_id);
 
         // Validate storage deposit
         let storage_update = self.new_storage_update(owner_id.clone());
 
-        let unwrap_balance: Balance = price.into();
-        let mut amount_need_to_pay: Balance = (max_participants as u128)
-            .checked_mul(unwrap_balance)
-            .expect("Cannot calculate total amount");
-        // Get 10% for other service
-        amount_need_to_pay = amount_need_to_pay + amount_need_to_pay / 10;
+        let deposit = env::attached_deposit();
+        let (budget, fee) = if task.token_id.is_none() {
+            let budget = self.internal_task_budget(task.price.into(), task.max_participants);
+            (budget, self.internal_task_fee(budget))
+        } else {
+            (0, 0)
+        };
+        if deposit > 0 {
+            // Storage is only known after the task is created, it is checked below
+            assert!(
+                deposit >= budget + fee,
+                "Attach at least {} yoctoNEAR: {} for the budget, {} for the fee, plus storage",
+                budget + fee,
+                budget,
+                fee
+            );
+            self.internal_send(Some(owner_id.clone()), deposit);
+        }
+
+        let task_id = self.internal_create_task(&owner_id, task);
+
+        if deposit > 0 {
+            let storage_cost = storage_update.cost();
+            assert!(
+                deposit >= budget + fee + storage_cost,
+                "Attach {} yoctoNEAR: {} for the budget, {} for the fee, {} for storage",
+                budget + fee + storage_cost,
+                budget,
+                fee,
+                storage_cost
+            );
+            self.internal_storage_deposit(&owner_id, storage_cost);
+        }
+
+        self.finalize_storage_update(storage_update);
+        task_id
+    }
+
+    // Open a draft or a scheduled task for submissions right now
+    pub fn publish_task(&mut self, task_id: TaskId) {
+        let mut task = self.internal_get_task(&task_id);
+        let now = env::block_timestamp();
+
+        assert_eq!(
+            task.owner,
+            env::predecessor_account_id(),
+            "Only owner can publish this task"
+        );
+        assert!(
+            task.status == TaskStatus::Draft
+                || (task.status == TaskStatus::Open && task.starts_at > now),
+            "Task is already published"
+        );
+
+        // Keep the same duration when a draft was published late
+        if task.starts_at < now {
+            let delay = now - task.starts_at;
+            task.submit_available_until += delay;
+            task.milestones
+                .iter_mut()
+                .for_each(|milestone| milestone.deadline += delay);
+        }
+
+        if task.status == TaskStatus::Draft {
+            self.internal_collect_fee(&task.token_id, task.fee);
+        }
+
+        task.starts_at = now;
+        task.status = TaskStatus::Open;
+        self.task_recores.insert(&task_id, &task);
+    }
+
+    // Remove a draft and refund its full budget
+    pub fn discard_draft(&mut self, task_id: TaskId) {
+        let owner_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(owner_id.clone());
+
+        let mut task = self.internal_get_task(&task_id);
+        assert_eq!(task.owner, owner_id, "Only owner can discard this task");
+        assert!(task.status == TaskStatus::Draft, "Task is not a draft");
+
+        if let Some(mut invitees) = task.invitees.take() {
+            for account_id in invitees.iter() {
+                if let Some(mut account) = self.internal_get_account_optional(&account_id) {
+                    account.invited_tasks.remove(&task_id);
+                    self.internal_set_account(&account_id, account);
+                }
+            }
+            invitees.clear();
+        }
+        task.amendments.clear();
 
         let mut category = self
             .categories
-            .get(&category_id)
+            .get(&task.category_id)
             .expect("Category not found");
-        let task_id = env::predecessor_account_id() + "_" + &env::block_index().to_string();
+        category.num_posts -= 1;
+        self.categories.insert(&task.category_id, &category);
 
-        assert!(
-            self.task_recores.get(&task_id).is_none(),
-            "Can't post twice per block"
+        let mut owner = self.internal_get_account(&owner_id);
+        owner.current_jobs.remove(&task_id);
+        self.internal_set_account(&owner_id, owner);
+
+        // Draft was never published, its fee was not collected and is refunded as well
+        self.task_recores.remove(&task_id);
+        self.internal_payout(&owner_id, &task.token_id, task.buget + task.fee);
+        env::log(
+            format!(
+                "Refunded {} to {}, including the platform fee of {}",
+                task.buget + task.fee,
+                owner_id,
+                task.fee
+            )
+            .as_bytes(),
         );
 
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Create all tasks or none of them
+    pub fn new_tasks(&mut self, tasks: Vec<TaskInput>) -> Vec<TaskId> {
+        let owner_id = env::predecessor_account_id();
+
+        // Validate storage deposit
+        let storage_update = self.new_storage_update(owner_id.clone());
+
+        assert!(!tasks.is_empty(), "No task to create");
         assert!(
-            amount_need_to_pay >= self.app_config.minimum_reward_per_task
-                && amount_need_to_pay <= self.app_config.maximum_reward_per_task,
-            "Total amount for each task must be in a range from {} to {}",
-            self.app_config.minimum_reward_per_task,
-            self.app_config.maximum_reward_per_task
+            tasks.len() <= self.app_config.maximum_tasks_per_batch.into(),
+            "Only accept {} tasks per batch",
+            self.app_config.maximum_tasks_per_batch
         );
 
+        // Validate total budget before creating any task, token tasks are paid one by one
+        let total_amount: Balance = tasks
+            .iter()
+            .filter(|task| task.token_id.is_none())
+            .map(|task| {
+                let budget = self.internal_task_budget(task.price.into(), task.max_participants);
+                budget + self.internal_task_fee(budget)
+            })
+            .sum();
         assert!(
-            description.len() <= self.app_config.maximum_description_length.into(),
-            "Description too long"
+            total_amount <= self.internal_available_balance(&owner_id),
+            "Balance not available to pay {} for {} tasks",
+            total_amount,
+            tasks.len()
         );
 
+        let task_ids = tasks
+            .into_iter()
+            .map(|task| self.internal_create_task(&owner_id, task))
+            .collect();
+
+        self.finalize_storage_update(storage_update);
+        task_ids
+    }
+
+    // Description and price can only be changed while no proposal has been approved
+    pub fn update_task(
+        &mut self,
+        task_id: TaskId,
+        description: Option<String>,
+        extend_duration: Option<WrappedDuration>,
+        price: Option<WrappedBalance>,
+        max_participants: Option<u16>,
+    ) {
+        let owner_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(owner_id.clone());
+
+        let mut task = self.internal_get_task(&task_id);
+        assert_eq!(task.owner, owner_id, "Only owner can update this task");
         assert!(
-            max_participants <= self.app_config.maximum_proposals_at_one_time,
-            "Only accept {} participants at one time",
-            self.app_config.maximum_proposals_at_one_time
+            matches!(task.status, TaskStatus::Draft | TaskStatus::Open),
+            "Task is already closed"
+        );
+        assert!(
+            description.is_some()
+                || extend_duration.is_some()
+                || price.is_some()
+                || max_participants.is_some(),
+            "Nothing to update"
         );
 
-        // Using balance to create task
-        self.internal_payment(amount_need_to_pay);
+        let has_approvals = self.internal_count_approvals(&task) > 0;
+        let mut amendment = TaskAmendment {
+            amended_at: env::block_timestamp(),
+            description: None,
+            submit_available_until: None,
+            price: None,
+            max_participants: None,
+        };
 
-        let unwrap_duration: Duration = duration.into();
+        if let Some(description) = description {
+            assert!(!has_approvals, "Cannot edit a task after approving a proposal");
+            assert!(
+                description.len() <= self.app_config.maximum_description_length.into(),
+                "Description too long"
+            );
+            task.description = description.clone();
+            amendment.description = Some(description);
+        }
 
-        let task = Task {
-            owner: owner_id.clone(),
-            title,
-            description,
-            price: price.into(),
-            max_participants,
-            buget: amount_need_to_pay,
-            proposals: Vec::new(),
-            created_at: env::block_timestamp(),
-            submit_available_until: env::block_timestamp() + unwrap_duration,
-            category_id: category_id.clone(),
-            last_rejection_published_at: None,
-        };
+        if let Some(extend_duration) = extend_duration {
+            let unwrap_duration: Duration = extend_duration.into();
+            assert!(unwrap_duration > 0, "Can only extend the deadline");
+            // Commitments may already be revealed once the reveal window is open
+            assert!(
+                task.reveal_interval.is_none()
+                    || task.submit_available_until > env::block_timestamp(),
+                "Can't extend the deadline after commitments start to be revealed"
+            );
+            task.submit_available_until =
+                std::cmp::max(task.submit_available_until, env::block_timestamp())
+                    + unwrap_duration;
+            amendment.submit_available_until = Some(task.submit_available_until);
+        }
 
-        //Update num_posts in category
-        category.num_posts += 1;
-        self.categories.insert(&category_id, &category);
+        if let Some(price) = price {
+            let unwrap_balance: Balance = price.into();
+            assert!(!has_approvals, "Cannot change price after approving a proposal");
+            assert!(task.milestones.is_empty(), "Cannot change price of a milestone task");
+            assert!(unwrap_balance > task.price, "Can only raise the price");
+            task.price = unwrap_balance;
+            amendment.price = Some(unwrap_balance);
+        }
 
-        // Add task to task recores
-        self.task_recores.insert(&task_id, &task);
+        if let Some(max_participants) = max_participants {
+            assert!(
+                max_participants > task.max_participants,
+                "Can only raise the number of participants"
+            );
+            assert!(
+                max_participants <= self.app_config.maximum_proposals_at_one_time,
+                "Only accept {} participants at one time",
+                self.app_config.maximum_proposals_at_one_time
+            );
+            task.max_participants = max_participants;
+            amendment.max_participants = Some(max_participants);
+        }
 
-        // Update Owner Account
-        owner.current_jobs.insert(&task_id);
-        self.internal_set_account(&owner_id, owner);
+        // Top up buget for the new price and seats
+        let amount_need_to_pay = self.internal_task_budget(task.price, task.max_participants);
+        if amount_need_to_pay > task.buget {
+            assert!(
+                task.token_id.is_some()
+                    || amount_need_to_pay <= self.app_config.maximum_reward_per_task,
+                "Total amount for each task must be in a range from {} to {}",
+                self.app_config.minimum_reward_per_task,
+                self.app_config.maximum_reward_per_task
+            );
+            let fee = self.internal_task_fee(amount_need_to_pay - task.buget);
+            self.internal_charge(&task.owner, &task.token_id, amount_need_to_pay - task.buget + fee);
+            if task.status != TaskStatus::Draft {
+                self.internal_collect_fee(&task.token_id, fee);
+            }
+            task.buget = amount_need_to_pay;
+            task.fee += fee;
+        }
+
+        task.amendments.push(&amendment);
+        self.task_recores.insert(&task_id, &task);
 
         self.finalize_storage_update(storage_update);
     }
 
-    pub fn approve_work(&mut self, task_id: TaskId, worker_id: AccountId) {
+    pub fn approve_work(&mut self, task_id: TaskId, worker_id: AccountId, milestone: Option<u8>) {
         let storage_update = self.new_storage_update(worker_id.clone());
 
         // Check task condition
-        let task = self.internal_get_task(&task_id);
-        assert!(
-            task.proposals
-                .iter()
-                .filter(|proposal_id| self
-                    .proposals
-                    .get(proposal_id)
-                    .expect("Proposal not found")
-                    .status
-                    == ProposalStatus::Approved)
-                .count()
-                < task.max_participants.into(),
-            "You have approved for {} participants",
-            task.max_participants
-        );
-
+        let mut task = self.internal_get_task(&task_id);
         assert!(
             task.owner == env::predecessor_account_id(),
             "Only owner can approve proposal"
         );
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
 
         // Check proposal condition
+        let (_, proposal) = self.internal_get_proposal(task_id.clone(), worker_id.clone());
+        assert!(
+            *proposal.status_of(milestone) != ProposalStatus::Committed,
+            "Work is not revealed yet"
+        );
+        assert!(
+            proposal.status_of(milestone).is_in_review(),
+            "You already approved or rejected this worker!!"
+        );
+
+        self.internal_approve(&task_id, &mut task, &worker_id, milestone);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Anyone can approve a submission the owner ignored for longer than the review interval
+    pub fn auto_approve(&mut self, task_id: TaskId, worker_id: AccountId, milestone: Option<u8>) {
+        let storage_update = self.new_storage_update(worker_id.clone());
+
+        let mut task = self.internal_get_task(&task_id);
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
+
+        let (_, proposal) = self.internal_get_proposal(task_id.clone(), worker_id.clone());
+        assert!(
+            *proposal.status_of(milestone) == ProposalStatus::Pending,
+            "Only pending proposal can be auto approved"
+        );
+
+        let submit_time = match milestone {
+            Some(index) => proposal.milestones[index as usize].submit_time,
+            None => proposal.submit_time,
+        };
+        assert!(
+            submit_time + self.app_config.review_interval <= env::block_timestamp(),
+            "Owner can still review this proposal"
+        );
+
+        self.internal_approve(&task_id, &mut task, &worker_id, milestone);
+
+        // Owner ignored the proposal
+        let mut owner = self.internal_get_account(&task.owner);
+        owner.add_neg_point(self.app_config.med_minus as u32);
+        self.internal_set_account(&task.owner, owner);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Paid from the owner's wallet, or from the buget which is not owed to workers yet
+    pub fn tip(
+        &mut self,
+        task_id: TaskId,
+        worker_id: AccountId,
+        amount: WrappedBalance,
+        from_budget: Option<bool>,
+    ) {
+        let owner_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(owner_id.clone());
+        let amount: Balance = amount.into();
+
+        let mut task = self.internal_get_task(&task_id);
+        assert_eq!(task.owner, owner_id, "Only owner can tip workers");
+        assert!(amount > 0, "Tip must be greater than 0");
+
         let (proposal_id, mut proposal) =
             self.internal_get_proposal(task_id.clone(), worker_id.clone());
-
         assert!(
-            proposal.status == ProposalStatus::Pending,
-            "You already approved or rejected this worker!!"
+            proposal.all_statuses().any(|status| matches!(
+                status,
+                ProposalStatus::Approved | ProposalStatus::ApprovedByAdmin { .. }
+            )),
+            "Only approved workers can be tipped"
         );
 
-        // Update proposal
-        proposal.status = ProposalStatus::Approved;
-        self.proposals.insert(&proposal_id, &proposal);
+        if from_budget.unwrap_or(false) {
+            assert!(task.status == TaskStatus::Open, "Task is already closed");
+            assert!(
+                amount <= task.buget - self.internal_owed_amount(&task),
+                "Tip is larger than the remaining buget"
+            );
+            task.buget -= amount;
+            self.task_recores.insert(&task_id, &task);
+        } else {
+            self.internal_charge(&owner_id, &task.token_id, amount);
+        }
+
+        let mut owner = self.internal_get_account(&owner_id);
+        if task.token_id.is_none() {
+            owner.total_spent += amount;
+        }
+        self.internal_set_account(&owner_id, owner);
 
-        // Set locked balance for worker
         let mut worker = self.internal_get_account(&worker_id);
-        let release_at: Timestamp = match task.last_rejection_published_at {
-            Some(time) => {
-                time + self.app_config.report_interval + self.app_config.validate_report_interval
-            }
-            None => env::block_timestamp(),
-        };
-        let new_locked_balance = LockedBalance {
-            amount: task.price,
-            release_at,
-            // Must be the last rejection deadline report + 3 days
-        };
-        worker.locked_balance.insert(&task_id, &new_locked_balance);
-        worker.add_pos_point(self.app_config.sml_plus as u32);
+        worker.add_earning(task_id, EarningKind::Tip, amount, task.token_id.clone());
+        // Point bonus is given once per task, more tips don't farm reputation
+        if !proposal.tipped {
+            worker.add_pos_point(self.app_config.sml_plus as u32);
+            proposal.tipped = true;
+            self.proposals.insert(&proposal_id, &proposal);
+        }
         self.internal_set_account(&worker_id, worker);
+        self.internal_payout(&worker_id, &task.token_id, amount);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Worker can resubmit, limited by maximum revision rounds
+    pub fn request_changes(&mut self, task_id: TaskId, worker_id: AccountId, note: String) {
+        let storage_update = self.new_storage_update(env::predecessor_account_id());
+
+        let mut task = self.internal_get_task(&task_id);
+        assert!(
+            task.owner == env::predecessor_account_id(),
+            "Only owner can request changes"
+        );
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
+        assert!(
+            task.milestones.is_empty(),
+            "Changes can only be requested on single delivery tasks"
+        );
+        assert!(
+            note.len() <= self.app_config.maximum_message_length.into(),
+            "Note too long"
+        );
+
+        let (proposal_id, mut proposal) = self.internal_get_proposal(task_id.clone(), worker_id);
+        assert!(
+            proposal.status == ProposalStatus::Pending,
+            "Proposal is not pending"
+        );
+        assert!(
+            proposal.revisions.len() < self.app_config.maximum_revision_rounds.into(),
+            "Reached {} revision rounds, approve or reject this proposal",
+            self.app_config.maximum_revision_rounds
+        );
+
+        proposal.status = ProposalStatus::ChangesRequested { note };
+        self.internal_set_proposal(&mut task, &proposal_id, &proposal);
+        self.task_recores.insert(&task_id, &task);
 
         self.finalize_storage_update(storage_update);
     }
 
     //TODO: add reason by owner CHECKED
-    pub fn reject_work(&mut self, task_id: TaskId, worker_id: AccountId, reason: String) {
+    pub fn reject_work(
+        &mut self,
+        task_id: TaskId,
+        worker_id: AccountId,
+        reason: String,
+        milestone: Option<u8>,
+    ) {
         let storage_update = self.new_storage_update(worker_id.clone());
 
         // Check task condition
         let mut task = self.internal_get_task(&task_id);
+        self.internal_milestone_amount(&task, milestone);
 
         assert!(
             task.owner == env::predecessor_account_id(),
             "Only owner can approve proposal"
         );
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
 
         // Check proposal condition
         let (proposal_id, mut proposal) = self.internal_get_proposal(task_id.clone(), worker_id);
 
         assert!(
-            proposal.status == ProposalStatus::Pending,
+            proposal.status_of(milestone).is_in_review(),
             "You already approved or rejected this worker!!"
         );
 
         // Update proposal
-        proposal.status = ProposalStatus::Rejected {
-            reason,
-            reject_at: env::block_timestamp(),
-            report_id: None,
-        };
-        self.proposals.insert(&proposal_id, &proposal);
+        proposal.set_status(
+            milestone,
+            ProposalStatus::Rejected {
+                reason,
+                reject_at: env::block_timestamp(),
+                report_id: None,
+            },
+        );
+        self.internal_set_proposal(&mut task, &proposal_id, &proposal);
 
         // Update task
         task.last_rejection_published_at = Some(env::block_timestamp());
@@ -181,109 +478,534 @@ impl Dwork {
         self.finalize_storage_update(storage_update);
     }
 
+    pub fn add_invitees(&mut self, task_id: TaskId, account_ids: Vec<AccountId>) {
+        let storage_update = self.new_storage_update(env::predecessor_account_id());
+
+        let mut task = self.internal_get_task(&task_id);
+        assert_eq!(
+            task.owner,
+            env::predecessor_account_id(),
+            "Only owner can invite workers"
+        );
+        assert!(
+            matches!(task.status, TaskStatus::Draft | TaskStatus::Open),
+            "Task is already closed"
+        );
+
+        self.internal_add_invitees(&task_id, &mut task, account_ids);
+        self.task_recores.insert(&task_id, &task);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    pub fn remove_invitees(&mut self, task_id: TaskId, account_ids: Vec<AccountId>) {
+        let storage_update = self.new_storage_update(env::predecessor_account_id());
+
+        let mut task = self.internal_get_task(&task_id);
+        assert_eq!(
+            task.owner,
+            env::predecessor_account_id(),
+            "Only owner can remove invited workers"
+        );
+
+        let invitees = task.invitees.as_mut().expect("Task is not invite only");
+        for account_id in account_ids {
+            assert!(invitees.remove(&account_id), "Account is not invited");
+            if let Some(mut account) = self.internal_get_account_optional(&account_id) {
+                account.invited_tasks.remove(&task_id);
+                self.internal_set_account(&account_id, account);
+            }
+        }
+        self.task_recores.insert(&task_id, &task);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Hiring closes when all seats are taken
+    pub fn hire(&mut self, task_id: TaskId, worker_ids: Vec<AccountId>) {
+        let storage_update = self.new_storage_update(env::predecessor_account_id());
+
+        let mut task = self.internal_get_task(&task_id);
+        assert_eq!(
+            task.owner,
+            env::predecessor_account_id(),
+            "Only owner can hire workers"
+        );
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
+        assert!(
+            task.hiring == Some(HiringStatus::Open),
+            "Task is not hiring"
+        );
+
+        for worker_id in worker_ids {
+            let (application_id, mut application) = self
+                .internal_get_application(task_id.clone(), worker_id)
+                .expect("Application not found");
+            assert!(
+                application.status == ApplicationStatus::Pending,
+                "Already hired or declined this worker"
+            );
+            application.status = ApplicationStatus::Hired;
+            self.applications.insert(&application_id, &application);
+        }
+
+        let num_hired = task
+            .applications
+            .iter()
+            .filter(|application_id| {
+                self.applications
+                    .get(application_id)
+                    .expect("Application not found")
+                    .status
+                    == ApplicationStatus::Hired
+            })
+            .count();
+        assert!(
+            num_hired <= task.max_participants.into(),
+            "Only can hire {} workers",
+            task.max_participants
+        );
+
+        if num_hired == task.max_participants as usize {
+            self.internal_close_hiring(&task_id, &mut task);
+        }
+        self.task_recores.insert(&task_id, &task);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Owner can close hiring any time, anyone can close it after the submit deadline
+    pub fn close_hiring(&mut self, task_id: TaskId) {
+        let mut task = self.internal_get_task(&task_id);
+        assert!(
+            task.owner == env::predecessor_account_id()
+                || task.submit_available_until < env::block_timestamp(),
+            "Only owner can close hiring before the deadline"
+        );
+
+        self.internal_close_hiring(&task_id, &mut task);
+        self.task_recores.insert(&task_id, &task);
+    }
+
     // Will refund remainder amount for owner
     // Just task owner can call this function
     pub fn mark_task_as_completed(&mut self, task_id: TaskId) {
         let storage_update = self.new_storage_update(env::predecessor_account_id());
 
         let task = self.internal_get_task(&task_id);
-        let mut owner = self.internal_get_account(&task.owner);
-
         assert_eq!(
             task.owner,
             env::predecessor_account_id(),
             "Only owner can mark this task as complete"
         );
+        self.internal_complete_task(&task_id, task, None);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Anyone can complete a task when its deadline and all report windows have passed,
+    // the caller gets a bounty from the remainder
+    pub fn finalize_task(&mut self, task_id: TaskId) {
+        let caller_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(caller_id.clone());
+
+        let task = self.internal_get_task(&task_id);
+        assert!(
+            task.submit_available_until
+                + self.app_config.report_interval
+                + self.app_config.validate_report_interval
+                < env::block_timestamp(),
+            "Task can't be finalized yet"
+        );
+        let finalizer = (caller_id != task.owner).then_some(caller_id);
+        self.internal_complete_task(&task_id, task, finalizer);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Close submissions and refund the budget which is not owed to approved workers.
+    // Pending workers get their submit bond back.
+    // Just task owner can call this function
+    pub fn cancel_task(&mut self, task_id: TaskId) {
+        let storage_update = self.new_storage_update(env::predecessor_account_id());
+
+        let mut task = self.internal_get_task(&task_id);
+
+        assert_eq!(
+            task.owner,
+            env::predecessor_account_id(),
+            "Only owner can cancel this task"
+        );
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
+        // Rejected workers still can report, wait until all disputes are resolved
+        self.internal_assert_no_open_disputes(&task);
+
+        for proposal_id in task.proposals.clone().iter() {
+            let mut proposal = self
+                .proposals
+                .get(proposal_id)
+                .expect("Proposal not found");
+
+            if proposal.status.is_in_review() || proposal.status == ProposalStatus::Committed {
+                let mut worker = self.internal_get_account(&proposal.account_id);
+                worker.current_jobs.remove(&task_id);
+                self.internal_set_account(&proposal.account_id, worker);
+
+                proposal.status = ProposalStatus::Cancelled;
+            }
+
+            // Milestones which are not approved yet won't be paid
+            proposal
+                .milestones
+                .iter_mut()
+                .filter(|item| item.status == ProposalStatus::Pending)
+                .for_each(|item| item.status = ProposalStatus::Cancelled);
+            self.internal_set_proposal(&mut task, proposal_id, &proposal);
+        }
+
+        // Refund bond of all workers and applicants
+        self.internal_settle_bonds(&task);
+        if task.hiring == Some(HiringStatus::Open) {
+            self.internal_close_hiring(&task_id, &mut task);
+        }
+        self.internal_refund_application_bonds(&task_id, &task);
+
+        let owed = self.internal_owed_amount(&task);
+        let refund = task.buget - owed;
+
+        // Loaded after the bonds are refunded so the owner's wallet is not overwritten
+        let mut owner = self.internal_get_account(&task.owner);
+        owner.current_jobs.remove(&task_id);
+        if task.token_id.is_none() {
+            owner.total_spent += owed;
+        }
+        self.internal_set_account(&task.owner, owner);
+        self.internal_payout(&task.owner, &task.token_id, refund);
+        env::log(
+            format!(
+                "Refunded {} to {}, the platform fee of {} is not refunded",
+                refund, task.owner, task.fee
+            )
+            .as_bytes(),
+        );
+
+        task.buget = owed;
+        task.submit_available_until =
+            std::cmp::min(task.submit_available_until, env::block_timestamp());
+        task.status = TaskStatus::Cancelled;
+        self.task_recores.insert(&task_id, &task);
+
+        self.finalize_storage_update(storage_update);
+    }
+}
+
+impl Dwork {
+    // Skip ids which were generated by the old block index scheme
+    pub(crate) fn internal_gen_task_id(&mut self, owner_id: &AccountId) -> TaskId {
+        loop {
+            let task_id = owner_id.clone() + "_" + &self.next_task_nonce.to_string();
+            self.next_task_nonce += 1;
+            if self.task_recores.get(&task_id).is_none() {
+                return task_id;
+            }
+        }
+    }
+
+    // Validate the input and pay the task budget from owner's balance
+    pub(crate) fn internal_create_task(&mut self, owner_id: &AccountId, input: TaskInput) -> TaskId {
+        let TaskInput {
+            title,
+            description,
+            price,
+            max_participants,
+            duration,
+            category_id,
+            milestones,
+            hiring,
+            invitees,
+            starts_at,
+            draft,
+            requirements,
+            reveal_interval,
+            token_id,
+        } = input;
+        let starts_at = std::cmp::max(
+            env::block_timestamp(),
+            starts_at.map(|item| item.into()).unwrap_or(0),
+        );
+
+        let amount_need_to_pay = self.internal_task_budget(price.into(), max_participants);
+
+        let mut category = self
+            .categories
+            .get(&category_id)
+            .expect("Category not found");
+        let task_id = self.internal_gen_task_id(owner_id);
+
+        // Reward range is in NEAR
+        if let Some(token_id) = &token_id {
+            assert!(
+                self.whitelisted_tokens.contains(token_id),
+                "Token is not whitelisted"
+            );
+            assert!(amount_need_to_pay > 0, "Price must be greater than 0");
+        } else {
+            assert!(
+                amount_need_to_pay >= self.app_config.minimum_reward_per_task
+                    && amount_need_to_pay <= self.app_config.maximum_reward_per_task,
+                "Total amount for each task must be in a range from {} to {}",
+                self.app_config.minimum_reward_per_task,
+                self.app_config.maximum_reward_per_task
+            );
+        }
+
         assert!(
-            task.last_rejection_published_at.is_none()
-                || task.last_rejection_published_at.unwrap()
-                    + self.app_config.report_interval
-                    + self.app_config.validate_report_interval
-                    < env::block_timestamp(),
-            "Task still in progress"
+            description.len() <= self.app_config.maximum_description_length.into(),
+            "Description too long"
         );
+
         assert!(
-            task.proposals
-                .iter()
-                .filter(|proposal_id| {
-                    match self
-                        .proposals
-                        .get(proposal_id)
-                        .expect("Proposal not found")
-                        .status
-                    {
-                        ProposalStatus::Rejected {
-                            reason: _,
-                            reject_at: _,
-                            report_id,
-                        } => report_id.is_some(),
-                        _ => false,
-                    }
-                })
-                .count()
-                == 0,
-            "Task still in progress"
+            max_participants <= self.app_config.maximum_proposals_at_one_time,
+            "Only accept {} participants at one time",
+            self.app_config.maximum_proposals_at_one_time
         );
 
-        let reports_by = task
-            .proposals
-            .iter()
-            .filter_map(|proposal_id| {
-                (matches!(
-                    self.proposals
-                        .get(proposal_id)
-                        .expect("Proposal not found")
-                        .status,
-                    ProposalStatus::ApprovedByAdmin { account_id: _ }
-                        | ProposalStatus::RejectedByAdmin { account_id: _ }
-                ))
-                .then(|| {
-                    match self
-                        .proposals
-                        .get(proposal_id)
-                        .expect("Proposal not found")
-                        .status
-                    {
-                        ProposalStatus::ApprovedByAdmin { account_id } => account_id,
-                        ProposalStatus::RejectedByAdmin { account_id } => account_id,
-                        _ => "".to_string(),
-                    }
-                })
+        let milestones: Vec<Milestone> = milestones
+            .unwrap_or_default()
+            .into_iter()
+            .map(|item| {
+                let amount: Balance = item.amount.into();
+                let milestone_duration: Duration = item.duration.into();
+                assert!(amount > 0, "Milestone amount must be greater than 0");
+                assert!(
+                    item.deliverable.len() <= self.app_config.maximum_description_length.into(),
+                    "Milestone deliverable too long"
+                );
+                Milestone {
+                    deliverable: item.deliverable,
+                    amount,
+                    deadline: starts_at + milestone_duration,
+                }
             })
-            .collect::<Vec<String>>();
+            .collect();
+
+        assert!(
+            reveal_interval.is_none() || milestones.is_empty(),
+            "Milestone tasks can't use commit-reveal submissions"
+        );
+
+        if !milestones.is_empty() {
+            assert!(
+                milestones.len() <= self.app_config.maximum_milestones_per_task.into(),
+                "Only accept {} milestones per task",
+                self.app_config.maximum_milestones_per_task
+            );
+            assert_eq!(
+                milestones.iter().map(|item| item.amount).sum::<Balance>(),
+                Balance::from(price),
+                "Milestone amounts must add up to the price"
+            );
+        }
+
+        // Using balance to create task, the fee of a draft is held with its buget until it's published
+        let fee = self.internal_task_fee(amount_need_to_pay);
+        self.internal_charge(owner_id, &token_id, amount_need_to_pay + fee);
+        if !draft.unwrap_or(false) {
+            self.internal_collect_fee(&token_id, fee);
+        }
+
+        let unwrap_duration: Duration = duration.into();
+
+        let mut task = Task {
+            owner: owner_id.clone(),
+            title,
+            description,
+            price: price.into(),
+            max_participants,
+            buget: amount_need_to_pay,
+            fee,
+            proposals: Vec::new(),
+            proposal_counts: BTreeMap::new(),
+            created_at: env::block_timestamp(),
+            starts_at,
+            submit_available_until: starts_at + unwrap_duration,
+            category_id: category_id.clone(),
+            last_rejection_published_at: None,
+            status: if draft.unwrap_or(false) {
+                TaskStatus::Draft
+            } else {
+                TaskStatus::Open
+            },
+            amendments: Vector::new(StorageKey::TaskAmendments {
+                task_id: task_id.clone(),
+            }),
+            milestones,
+            hiring: hiring.unwrap_or(false).then_some(HiringStatus::Open),
+            applications: Vec::new(),
+            invitees: invitees.as_ref().map(|_| {
+                UnorderedSet::new(StorageKey::TaskInvitees {
+                    task_id: task_id.clone(),
+                })
+            }),
+            requirements,
+            reveal_interval: reveal_interval.map(|item| item.into()),
+            token_id,
+        };
+
+        if let Some(invitees) = invitees {
+            self.internal_add_invitees(&task_id, &mut task, invitees);
+        }
+
+        //Update num_posts in category
+        category.num_posts += 1;
+        self.categories.insert(&category_id, &category);
 
-        let refund: u64 = task
+        // Add task to task recores
+        self.task_recores.insert(&task_id, &task);
+
+        // Update Owner Account, loaded after the payment so the wallet is not overwritten
+        let mut owner = self.internal_get_account(owner_id);
+        owner.current_jobs.insert(&task_id);
+        self.internal_set_account(owner_id, owner);
+
+        task_id
+    }
+
+    pub(crate) fn internal_complete_task(
+        &mut self,
+        task_id: &TaskId,
+        mut task: Task,
+        finalizer: Option<AccountId>,
+    ) {
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
+        self.internal_assert_no_open_disputes(&task);
+        if let Some(reveal_interval) = task.reveal_interval {
+            assert!(
+                env::block_timestamp() > task.submit_available_until + reveal_interval,
+                "Workers can still reveal their work"
+            );
+        }
+
+        self.internal_settle_bonds(&task);
+        if task.hiring == Some(HiringStatus::Open) {
+            self.internal_close_hiring(task_id, &mut task);
+        }
+        self.internal_refund_application_bonds(task_id, &task);
+
+        let reports_by = task
             .proposals
             .iter()
-            .filter(|proposal_id| {
+            .flat_map(|proposal_id| {
                 self.proposals
                     .get(proposal_id)
                     .expect("Proposal not found")
-                    .status
-                    == ProposalStatus::Approved
+                    .all_statuses()
+                    .filter_map(|status| match status {
+                        ProposalStatus::ApprovedByAdmin { account_id }
+                        | ProposalStatus::RejectedByAdmin { account_id } => {
+                            Some(account_id.clone())
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<String>>()
             })
-            .count() as u64;
+            .collect::<Vec<String>>();
+
+        let mut remainder = task.buget - self.internal_owed_amount(&task);
+
+        // Bounty for who finalized the task on behalf of the owner.
+        // It is set in NEAR, so tasks paid with a token only give the point bonus
+        let completed_by_owner = finalizer.is_none();
+        if let Some(finalizer) = finalizer {
+            if task.token_id.is_none() {
+                let bounty = std::cmp::min(self.app_config.finalize_bounty, remainder);
+                remainder -= bounty;
+                self.internal_send(Some(finalizer.clone()), bounty);
+            }
 
-        let mut remainder = task.buget
-            - (task.price)
-                .checked_mul(refund.into())
-                .expect("Can not calculate amount to refund");
+            let mut finalizer_account = self.internal_get_account(&finalizer);
+            finalizer_account.add_pos_point(self.app_config.claim_point_bonus);
+            self.internal_set_account(&finalizer, finalizer_account);
+        }
 
         if !reports_by.is_empty() {
             let amount = remainder / (reports_by.len() as u128);
             reports_by
                 .iter()
-                .for_each(|account_id| self.internal_send(Some(account_id.to_string()), amount));
+                .for_each(|account_id| self.internal_payout(account_id, &task.token_id, amount));
             remainder = 0;
         }
 
-        owner.completed_jobs.insert(&task_id);
-        owner.current_jobs.remove(&task_id);
-        owner.total_spent += task.buget - remainder;
-        owner.add_pos_point(self.app_config.sml_plus as u32);
+        // Loaded after the bonds and payouts so the owner's wallet is not overwritten
+        let mut owner = self.internal_get_account(&task.owner);
+        if completed_by_owner {
+            owner.add_pos_point(self.app_config.sml_plus as u32);
+        }
+        owner.completed_jobs.insert(task_id);
+        owner.current_jobs.remove(task_id);
+        if task.token_id.is_none() {
+            owner.total_spent += task.buget - remainder;
+        }
         self.internal_set_account(&task.owner, owner);
-        self.internal_send(None, remainder);
+        self.internal_payout(&task.owner, &task.token_id, remainder);
+        env::log(
+            format!(
+                "Refunded {} to {}, the platform fee of {} is not refunded",
+                remainder, task.owner, task.fee
+            )
+            .as_bytes(),
+        );
 
-        self.finalize_storage_update(storage_update);
+        task.status = TaskStatus::Completed;
+        self.task_recores.insert(task_id, &task);
+    }
+
+    // Proposal's status must be checked by the caller
+    pub(crate) fn internal_approve(
+        &mut self,
+        task_id: &TaskId,
+        task: &mut Task,
+        worker_id: &AccountId,
+        milestone: Option<u8>,
+    ) {
+        let amount = self.internal_milestone_amount(task, milestone);
+        let (proposal_id, mut proposal) =
+            self.internal_get_proposal(task_id.clone(), worker_id.clone());
+
+        // Worker takes a seat on the first approval
+        if proposal.status.is_in_review() {
+            assert!(
+                task.proposal_count(ProposalStatusKind::Approved) < task.max_participants.into(),
+                "You have approved for {} participants",
+                task.max_participants
+            );
+            proposal.status = ProposalStatus::Approved;
+        }
+
+        // Update proposal
+        proposal.set_status(milestone, ProposalStatus::Approved);
+        self.internal_set_proposal(task, &proposal_id, &proposal);
+        self.task_recores.insert(task_id, task);
+
+        // Set locked balance for worker
+        let mut worker = self.internal_get_account(worker_id);
+        let release_at: Timestamp = match task.last_rejection_published_at {
+            Some(time) => {
+                time + self.app_config.report_interval + self.app_config.validate_report_interval
+            }
+            None => env::block_timestamp(),
+        };
+        let new_locked_balance = LockedBalance {
+            amount,
+            release_at,
+            token_id: task.token_id.clone(),
+            // Must be the last rejection deadline report + 3 days
+        };
+        worker.locked_balance.insert(
+            &self.internal_gen_milestone_key(task_id, milestone),
+            &new_locked_balance,
+        );
+        worker.add_pos_point(self.app_config.sml_plus as u32);
+        self.internal_set_account(worker_id, worker);
     }
 }
@@ -67,23 +67,6 @@ impl StorageManager for Dwork {
         assert_one_yocto();
         let account_id = env::predecessor_account_id();
 
-        //TODO: withdraw condition
-        let account = self.internal_get_account(&account_id);
-        assert!(
-            account.pos_point > 50,
-            "Account must have positive point higher than {}",
-            50
-        );
-
-        if account.neg_point != 0 {
-            let rate = account.pos_point / account.neg_point;
-            assert!(
-                rate > self.app_config.critical_point as u32,
-                "Account must have positive point / negative point higher than {}",
-                self.app_config.critical_point
-            );
-        }
-
         let storage_balance = self.storage_balance_of((account_id.as_str()).try_into().unwrap());
         let amount: Balance = amount.unwrap_or(storage_balance.available).into();
         if amount > storage_balance.available.0 {
@@ -123,10 +106,17 @@ impl StorageManager for Dwork {
 
 pub(crate) struct StorageUpdate {
     account_id: AccountId,
-    storage_account: StorageAccount,
     initial_storage: StorageUsage,
 }
 
+impl StorageUpdate {
+    // Cost of the bytes added since the update started
+    pub fn cost(&self) -> Balance {
+        Balance::from(env::storage_usage().saturating_sub(self.initial_storage))
+            * STORAGE_PRICE_PER_BYTE
+    }
+}
+
 impl StorageAccount {
     pub fn assert_enough_balance(&self) {
         assert!(
@@ -145,14 +135,13 @@ impl Dwork {
 
 impl Dwork {
     pub(crate) fn new_storage_update(&mut self, account_id: AccountId) -> StorageUpdate {
-        let storage_account = self
-            .storage_accounts
-            .get(&account_id)
-            .expect("Account is not registered");
+        assert!(
+            self.storage_accounts.get(&account_id).is_some(),
+            "Account is not registered"
+        );
         let initial_storage = env::storage_usage();
         StorageUpdate {
             account_id,
-            storage_account,
             initial_storage,
         }
     }
@@ -160,31 +149,21 @@ impl Dwork {
     pub(crate) fn finalize_storage_update(&mut self, storage_update: StorageUpdate) {
         let StorageUpdate {
             account_id,
-            mut storage_account,
             initial_storage,
         } = storage_update;
+        // Re-read the account so payments and refunds made during the update are kept
+        let mut storage_account = self.storage_accounts.get(&account_id).unwrap();
         storage_account.used_bytes =
             (storage_account.used_bytes + env::storage_usage()).saturating_sub(initial_storage);
         storage_account.assert_enough_balance();
         self.storage_accounts.insert(&account_id, &storage_account);
     }
 
-    pub(crate) fn internal_payment(&mut self, amount: Balance) {
-        let account_id = env::predecessor_account_id();
-        let storage_balance = self.storage_balance_of((account_id.as_str()).try_into().unwrap());
-        assert!(
-            amount <= storage_balance.available.0,
-            "Balance not available to pay for internal request",
-        );
-        let mut storage_account = self.storage_accounts.get(&account_id).unwrap();
-        storage_account.balance -= amount;
-        self.storage_accounts.insert(&account_id, &storage_account);
-    }
-
-    pub(crate) fn internal_send(&mut self, account_id: Option<AccountId>, amount: Balance) {
-        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
-        let mut storage_account = self.storage_accounts.get(&account_id).unwrap();
+    // Move an amount from the wallet to the storage collateral
+    pub(crate) fn internal_storage_deposit(&mut self, account_id: &AccountId, amount: Balance) {
+        self.internal_payment(Some(account_id.clone()), amount);
+        let mut storage_account = self.storage_accounts.get(account_id).unwrap();
         storage_account.balance += amount;
-        self.storage_accounts.insert(&account_id, &storage_account);
+        self.storage_accounts.insert(account_id, &storage_account);
     }
 }
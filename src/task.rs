@@ -1,7 +1,114 @@
 use crate::*;
+use std::collections::BTreeMap;
 
 pub type TaskId = String;
 
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TaskStatus {
+    Draft,
+    Open,
+    Completed,
+    Cancelled,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Milestone {
+    pub deliverable: String,
+    pub amount: Balance,
+    pub deadline: Timestamp,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WrappedMilestone {
+    pub deliverable: String,
+    pub amount: WrappedBalance,
+    pub deadline: WrappedTimestamp,
+}
+
+impl From<Milestone> for WrappedMilestone {
+    fn from(milestone: Milestone) -> Self {
+        WrappedMilestone {
+            deliverable: milestone.deliverable,
+            amount: milestone.amount.into(),
+            deadline: milestone.deadline.into(),
+        }
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MilestoneInput {
+    pub deliverable: String,
+    pub amount: WrappedBalance,
+    pub duration: WrappedDuration,
+}
+
+// Workers must meet all of the requirements to submit or apply
+#[derive(BorshSerialize, BorshDeserialize, Debug, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskRequirements {
+    pub min_pos_point: Option<u32>,
+    // Negative points in percent of positive points
+    pub max_neg_point_ratio: Option<u16>,
+    pub min_completed_jobs: Option<u64>,
+    pub min_account_age: Option<WrappedDuration>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskInput {
+    pub title: String,
+    pub description: String,
+    pub price: WrappedBalance,
+    pub max_participants: u16,
+    pub duration: WrappedDuration,
+    pub category_id: CategoryId,
+    pub milestones: Option<Vec<MilestoneInput>>,
+    pub hiring: Option<bool>,
+    pub invitees: Option<Vec<AccountId>>,
+    pub starts_at: Option<WrappedTimestamp>,
+    pub draft: Option<bool>,
+    pub requirements: Option<TaskRequirements>,
+    // Workers commit a hash before the deadline and reveal their work in this window
+    pub reveal_interval: Option<WrappedDuration>,
+    // NEP-141 token which pays the task, NEAR when not set
+    pub token_id: Option<AccountId>,
+}
+
+// Only the fields which have been changed are recorded
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct TaskAmendment {
+    pub amended_at: Timestamp,
+    pub description: Option<String>,
+    pub submit_available_until: Option<Timestamp>,
+    pub price: Option<Balance>,
+    pub max_participants: Option<u16>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WrappedTaskAmendment {
+    pub amended_at: WrappedTimestamp,
+    pub description: Option<String>,
+    pub submit_available_until: Option<WrappedTimestamp>,
+    pub price: Option<WrappedBalance>,
+    pub max_participants: Option<u16>,
+}
+
+impl From<TaskAmendment> for WrappedTaskAmendment {
+    fn from(amendment: TaskAmendment) -> Self {
+        WrappedTaskAmendment {
+            amended_at: amendment.amended_at.into(),
+            description: amendment.description,
+            submit_available_until: amendment.submit_available_until.map(WrappedTimestamp::from),
+            price: amendment.price.map(WrappedBalance::from),
+            max_participants: amendment.max_participants,
+        }
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct Task {
     pub owner: AccountId,
@@ -10,11 +117,49 @@ pub struct Task {
     pub max_participants: u16,
     pub price: Balance,
     pub buget: Balance,
+    // Platform fee paid on top of the buget
+    pub fee: Balance,
     pub proposals: Vec<ProposalId>,
+    // Number of proposals per status, updated on every status change
+    pub proposal_counts: BTreeMap<ProposalStatusKind, u64>,
     pub created_at: Timestamp,
+    // Submissions are accepted from this time
+    pub starts_at: Timestamp,
     pub last_rejection_published_at: Option<Timestamp>,
     pub submit_available_until: Timestamp,
     pub category_id: CategoryId,
+    pub status: TaskStatus,
+    pub amendments: Vector<TaskAmendment>,
+    // Price is split into ordered milestones when not empty
+    pub milestones: Vec<Milestone>,
+    // Workers must be hired before submitting when set
+    pub hiring: Option<HiringStatus>,
+    pub applications: Vec<ApplicationId>,
+    // Only invited accounts can submit when set
+    pub invitees: Option<UnorderedSet<AccountId>>,
+    pub requirements: Option<TaskRequirements>,
+    // Reveal window after submit_available_until for commit-reveal tasks
+    pub reveal_interval: Option<Duration>,
+    // Price and buget are in this token, NEAR when not set
+    pub token_id: Option<AccountId>,
+}
+
+impl Task {
+    pub fn proposal_count(&self, kind: ProposalStatusKind) -> u64 {
+        self.proposal_counts.get(&kind).copied().unwrap_or(0)
+    }
+
+    pub fn add_proposal_count(&mut self, kind: ProposalStatusKind) {
+        *self.proposal_counts.entry(kind).or_insert(0) += 1;
+    }
+
+    pub fn remove_proposal_count(&mut self, kind: ProposalStatusKind) {
+        let count = self.proposal_counts.get_mut(&kind).expect("Proposal count not found");
+        *count -= 1;
+        if *count == 0 {
+            self.proposal_counts.remove(&kind);
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -25,10 +170,22 @@ pub struct WrappedTask {
     pub description: String,
     pub max_participants: u16,
     pub price: WrappedBalance,
-    pub proposals: Vec<Proposal>,
+    pub fee: WrappedBalance,
+    // Number of proposals per status, see proposals_by_task for the proposals
+    pub proposal_counts: BTreeMap<ProposalStatusKind, u64>,
     pub created_at: WrappedTimestamp,
+    pub starts_at: WrappedTimestamp,
     pub available_until: WrappedTimestamp,
     pub category_id: CategoryId,
+    pub status: TaskStatus,
+    pub version: u64,
+    pub milestones: Vec<WrappedMilestone>,
+    pub hiring: Option<HiringStatus>,
+    pub num_applications: u64,
+    pub invite_only: bool,
+    pub requirements: Option<TaskRequirements>,
+    pub reveal_interval: Option<WrappedDuration>,
+    pub token_id: Option<AccountId>,
 }
 
 // impl From<Task> for WrappedTask {
@@ -41,29 +198,46 @@ impl Dwork {
             max_participants,
             price,
             buget: _,
-            proposals,
+            fee,
+            proposals: _,
+            proposal_counts,
             created_at,
+            starts_at,
             last_rejection_published_at: _,
             submit_available_until,
             category_id,
+            status,
+            amendments,
+            milestones,
+            hiring,
+            applications,
+            invitees,
+            requirements,
+            reveal_interval,
+            token_id,
         } = task;
         
-        let proposals: Vec<Proposal> = proposals
-            .iter()
-            .map(|item| self.proposals.get(item).expect("Proposal not found"))
-            .rev()
-            .collect();
-
         WrappedTask {
             owner,
             title,
             description,
             max_participants,
             price: WrappedBalance::from(price),
-            proposals,
+            fee: WrappedBalance::from(fee),
+            proposal_counts,
             created_at: WrappedTimestamp::from(created_at),
+            starts_at: WrappedTimestamp::from(starts_at),
             available_until: WrappedTimestamp::from(submit_available_until),
             category_id,
+            status,
+            version: amendments.len(),
+            milestones: milestones.into_iter().map(WrappedMilestone::from).collect(),
+            hiring,
+            num_applications: applications.len() as u64,
+            invite_only: invitees.is_some(),
+            requirements,
+            reveal_interval: reveal_interval.map(WrappedDuration::from),
+            token_id,
         }
     }
 }
@@ -74,6 +248,136 @@ impl Dwork {
         self.task_recores.get(task_id).expect("Task not found")
     }
 
+    pub(crate) fn internal_assert_invited(&self, task: &Task, account_id: &AccountId) {
+        if let Some(invitees) = &task.invitees {
+            assert!(invitees.contains(account_id), "This task is invite only");
+        }
+    }
+
+    pub(crate) fn internal_add_invitees(
+        &mut self,
+        task_id: &TaskId,
+        task: &mut Task,
+        account_ids: Vec<AccountId>,
+    ) {
+        let invitees = task.invitees.as_mut().expect("Task is not invite only");
+        for account_id in account_ids {
+            let mut account = self.internal_get_account(&account_id);
+            account.invited_tasks.insert(task_id);
+            self.internal_set_account(&account_id, account);
+            invitees.insert(&account_id);
+        }
+    }
+
+    // Platform fee is not included
+    pub(crate) fn internal_task_budget(&self, price: Balance, max_participants: u16) -> Balance {
+        (max_participants as u128)
+            .checked_mul(price)
+            .expect("Cannot calculate total amount")
+    }
+
+    pub(crate) fn internal_count_approvals(&self, task: &Task) -> usize {
+        task.proposals
+            .iter()
+            .filter(|proposal_id| {
+                matches!(
+                    self.proposals
+                        .get(proposal_id)
+                        .expect("Proposal not found")
+                        .status,
+                    ProposalStatus::Approved | ProposalStatus::ApprovedByAdmin { account_id: _ }
+                )
+            })
+            .count()
+    }
+
+    pub(crate) fn internal_assert_no_open_disputes(&self, task: &Task) {
+        assert!(
+            task.last_rejection_published_at.is_none()
+                || task.last_rejection_published_at.unwrap()
+                    + self.app_config.report_interval
+                    + self.app_config.validate_report_interval
+                    < env::block_timestamp(),
+            "Task still in progress"
+        );
+        assert!(
+            task.proposals
+                .iter()
+                .filter(|proposal_id| {
+                    self.proposals
+                        .get(proposal_id)
+                        .expect("Proposal not found")
+                        .all_statuses()
+                        .any(|status| match status {
+                            ProposalStatus::Rejected {
+                                reason: _,
+                                reject_at: _,
+                                report_id,
+                            } => report_id.is_some(),
+                            _ => false,
+                        })
+                })
+                .count()
+                == 0,
+            "Task still in progress"
+        );
+    }
+
+    // Amount owed to workers for approved proposals or milestones
+    pub(crate) fn internal_owed_amount(&self, task: &Task) -> Balance {
+        task.proposals
+            .iter()
+            .map(|proposal_id| {
+                let proposal = self.proposals.get(proposal_id).expect("Proposal not found");
+                if task.milestones.is_empty() {
+                    match proposal.status {
+                        ProposalStatus::Approved
+                        | ProposalStatus::ApprovedByAdmin { account_id: _ } => task.price,
+                        _ => 0,
+                    }
+                } else {
+                    proposal
+                        .milestones
+                        .iter()
+                        .zip(task.milestones.iter())
+                        .filter(|(submission, _)| {
+                            matches!(
+                                submission.status,
+                                ProposalStatus::Approved
+                                    | ProposalStatus::ApprovedByAdmin { account_id: _ }
+                            )
+                        })
+                        .map(|(_, milestone)| milestone.amount)
+                        .sum()
+                }
+            })
+            .sum()
+    }
+
+    // Amount to pay for the proposal or the milestone, panic if the milestone doesn't match the task
+    pub(crate) fn internal_milestone_amount(&self, task: &Task, milestone: Option<u8>) -> Balance {
+        match milestone {
+            Some(index) => {
+                task.milestones
+                    .get(index as usize)
+                    .expect("Milestone not found")
+                    .amount
+            }
+            None => {
+                assert!(task.milestones.is_empty(), "Milestone is required for this task");
+                task.price
+            }
+        }
+    }
+
+    // Key of a locked balance or report: task id, followed by milestone index if any
+    pub(crate) fn internal_gen_milestone_key(&self, task_id: &str, milestone: Option<u8>) -> String {
+        match milestone {
+            Some(index) => format!("{}#{}", task_id, index),
+            None => task_id.to_string(),
+        }
+    }
+
     pub(crate) fn internal_gen_proposal_id(&self, task_id: TaskId, worker_id: AccountId) -> String {
         task_id + "_" + &worker_id
     }
@@ -94,14 +398,15 @@ impl Dwork {
         )
     }
 
+    // Drafts are hidden
     pub fn available_tasks(&self, from_index: u64, limit: u64) -> Vec<(TaskId, WrappedTask)> {
         let tasks_id = self.task_recores.keys_as_vector();
 
         calculate_rev_limit(tasks_id.len(), from_index, limit)
-            .map(|index| {
-                let task_id = tasks_id.get(index as u64).unwrap();
+            .filter_map(|index| {
+                let task_id = tasks_id.get(index).unwrap();
                 let task = self.task_recores.get(&task_id).unwrap();
-                (task_id, self.json_from_task(task))
+                (task.status != TaskStatus::Draft).then(|| (task_id, self.json_from_task(task)))
             })
             .rev()
             .collect()
@@ -157,6 +462,44 @@ impl Dwork {
             .collect()
     }
 
+    pub fn invited_tasks(
+        &self,
+        account_id: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<(TaskId, WrappedTask)> {
+        let tasks_id = self
+            .accounts
+            .get(&account_id)
+            .expect("User not found")
+            .invited_tasks
+            .to_vec();
+
+        calculate_rev_limit(tasks_id.len() as u64, from_index, limit)
+            .map(|index| {
+                let key = tasks_id.get(index as usize).unwrap();
+                (
+                    key.clone(),
+                    self.json_from_task(self.internal_get_task(key)),
+                )
+            })
+            .rev()
+            .collect()
+    }
+
+    pub fn task_invitees(&self, task_id: TaskId, from_index: u64, limit: u64) -> Vec<AccountId> {
+        let invitees = self
+            .internal_get_task(&task_id)
+            .invitees
+            .expect("Task is not invite only");
+        let account_ids = invitees.as_vector();
+
+        calculate_rev_limit(account_ids.len(), from_index, limit)
+            .map(|index| account_ids.get(index).unwrap())
+            .rev()
+            .collect()
+    }
+
     pub fn task_by_id(&self, task_id: TaskId) -> WrappedTask {
         self.task_recores
             .get(&task_id)
@@ -170,6 +513,20 @@ impl Dwork {
             .collect()
     }
 
+    pub fn task_amendments(
+        &self,
+        task_id: TaskId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<WrappedTaskAmendment> {
+        let amendments = self.internal_get_task(&task_id).amendments;
+
+        calculate_rev_limit(amendments.len(), from_index, limit)
+            .map(|index| amendments.get(index).unwrap().into())
+            .rev()
+            .collect()
+    }
+
     pub fn maximum_participants_per_task(&self) -> u16 {
         self.app_config.maximum_proposals_at_one_time
     }
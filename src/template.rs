@@ -0,0 +1,148 @@
+use crate::*;
+
+pub type TemplateId = u64;
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TaskTemplate {
+    pub owner: AccountId,
+    pub task: TaskInput,
+    // Anyone can spawn the next task after this interval once the last one is closed
+    pub recurrence: Option<Duration>,
+    pub last_task_id: Option<TaskId>,
+    pub last_spawned_at: Option<Timestamp>,
+}
+
+#[near_bindgen]
+impl Dwork {
+    pub fn new_template(
+        &mut self,
+        task: TaskInput,
+        recurrence: Option<WrappedDuration>,
+    ) -> TemplateId {
+        let owner_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(owner_id.clone());
+
+        assert!(
+            self.categories.get(&task.category_id).is_some(),
+            "Category not found"
+        );
+        assert!(
+            task.description.len() <= self.app_config.maximum_description_length.into(),
+            "Description too long"
+        );
+
+        let recurrence: Option<Duration> = recurrence.map(|item| item.into());
+        assert!(
+            recurrence.is_none() || recurrence.unwrap() > 0,
+            "Recurrence must be greater than 0"
+        );
+
+        let template_id = self.next_template_id;
+        self.next_template_id += 1;
+
+        let template = TaskTemplate {
+            owner: owner_id.clone(),
+            task,
+            recurrence,
+            last_task_id: None,
+            last_spawned_at: None,
+        };
+        self.task_templates.insert(&template_id, &template);
+
+        let mut owner = self.internal_get_account(&owner_id);
+        owner.templates.insert(&template_id);
+        self.internal_set_account(&owner_id, owner);
+
+        self.finalize_storage_update(storage_update);
+        template_id
+    }
+
+    pub fn remove_template(&mut self, template_id: TemplateId) {
+        let owner_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(owner_id.clone());
+
+        let template = self.internal_get_template(template_id);
+        assert_eq!(template.owner, owner_id, "Only owner can remove this template");
+
+        self.task_templates.remove(&template_id);
+        let mut owner = self.internal_get_account(&owner_id);
+        owner.templates.remove(&template_id);
+        self.internal_set_account(&owner_id, owner);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Owner can spawn a task any time, anyone else only when the template is recurring
+    // Task budget is paid from owner's balance
+    pub fn spawn_task(&mut self, template_id: TemplateId) -> TaskId {
+        let mut template = self.internal_get_template(template_id);
+        let now = env::block_timestamp();
+
+        if template.owner != env::predecessor_account_id() {
+            let recurrence = template
+                .recurrence
+                .expect("Only owner can spawn a task from this template");
+            let last_task_id = template
+                .last_task_id
+                .as_ref()
+                .expect("Only owner can spawn the first task");
+            // A discarded draft is removed, so a missing task counts as closed
+            assert!(
+                self.task_recores.get(last_task_id).is_none_or(|task| matches!(
+                    task.status,
+                    TaskStatus::Completed | TaskStatus::Cancelled
+                )),
+                "Last task is still open"
+            );
+            assert!(
+                template.last_spawned_at.unwrap() + recurrence <= now,
+                "Too early to spawn the next task"
+            );
+        }
+
+        let storage_update = self.new_storage_update(template.owner.clone());
+
+        let task_id = self.internal_create_task(&template.owner, template.task.clone());
+        template.last_task_id = Some(task_id.clone());
+        template.last_spawned_at = Some(now);
+        self.task_templates.insert(&template_id, &template);
+
+        self.finalize_storage_update(storage_update);
+        task_id
+    }
+
+    pub fn template_by_id(&self, template_id: TemplateId) -> TaskTemplate {
+        self.internal_get_template(template_id)
+    }
+
+    pub fn templates_by_owner(
+        &self,
+        account_id: AccountId,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<(TemplateId, TaskTemplate)> {
+        let template_ids = self
+            .accounts
+            .get(&account_id)
+            .expect("User not found")
+            .templates
+            .to_vec();
+
+        calculate_rev_limit(template_ids.len() as u64, from_index, limit)
+            .map(|index| {
+                let key = template_ids[index as usize];
+                (key, self.internal_get_template(key))
+            })
+            .rev()
+            .collect()
+    }
+}
+
+impl Dwork {
+    pub(crate) fn internal_get_template(&self, template_id: TemplateId) -> TaskTemplate {
+        self.task_templates
+            .get(&template_id)
+            .expect("Template not found")
+    }
+}
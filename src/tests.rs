@@ -0,0 +1,2361 @@
+use crate::*;
+use near_sdk::json_types::{Base64VecU8, ValidAccountId, U128};
+use near_sdk::serde_json::{self, json, Value};
+use near_sdk::test_utils::{
+    accounts, get_created_receipts, testing_env_with_promise_results, VMContextBuilder,
+};
+use near_sdk::{testing_env, MockedBlockchain, PromiseOrValue, PromiseResult, VMContext};
+use std::convert::TryFrom;
+
+const NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+const DAY: Duration = 86_400_000_000_000;
+const START: Timestamp = 1_600_000_000_000_000_000;
+const SUBMIT_BOND: Balance = 10_000_000_000_000_000_000_000;
+
+fn contract_id() -> ValidAccountId {
+    ValidAccountId::try_from("dwork.testnet").unwrap()
+}
+
+// Mock NEP-141 token, its calls to ft_on_transfer are made with it as the predecessor
+fn token() -> ValidAccountId {
+    ValidAccountId::try_from("usdc.testnet").unwrap()
+}
+
+fn admin() -> ValidAccountId {
+    accounts(0)
+}
+
+fn owner() -> ValidAccountId {
+    accounts(1)
+}
+
+fn worker() -> ValidAccountId {
+    accounts(2)
+}
+
+fn other_worker() -> ValidAccountId {
+    accounts(3)
+}
+
+fn finalizer() -> ValidAccountId {
+    accounts(4)
+}
+
+fn treasury() -> ValidAccountId {
+    accounts(5)
+}
+
+fn context(predecessor: ValidAccountId, deposit: Balance, block_timestamp: Timestamp) -> VMContext {
+    VMContextBuilder::new()
+        .current_account_id(contract_id())
+        .predecessor_account_id(predecessor)
+        .attached_deposit(deposit)
+        .block_timestamp(block_timestamp)
+        .account_balance(1_000_000 * NEAR)
+        .build()
+}
+
+fn set_context(predecessor: ValidAccountId, deposit: Balance, block_timestamp: Timestamp) {
+    testing_env!(context(predecessor, deposit, block_timestamp));
+}
+
+// Callbacks are called by the contract itself with the result of the transfer
+fn set_callback_context(result: PromiseResult) {
+    testing_env_with_promise_results(context(contract_id(), 0, START), result);
+}
+
+fn setup() -> Dwork {
+    set_context(contract_id(), 0, START);
+    let mut contract = Dwork::new();
+    contract.add_admin(admin().into());
+    contract.new_category("Design".to_string());
+
+    set_context(admin(), 0, START);
+    contract.add_token(token().into());
+    contract.set_fee_config(None, Some(treasury().into()));
+
+    for account_id in [admin(), owner(), worker(), other_worker(), finalizer()] {
+        set_context(account_id.clone(), NEAR, START);
+        contract.storage_deposit(None);
+        deposit(&mut contract, account_id, 10 * NEAR);
+    }
+    contract
+}
+
+fn deposit(contract: &mut Dwork, account_id: ValidAccountId, amount: Balance) {
+    set_context(account_id, amount, START);
+    contract.deposit(None);
+}
+
+// Submissions, report windows and their validation have all passed
+fn finalize_at(contract: &Dwork) -> Timestamp {
+    START
+        + 7 * DAY
+        + contract.app_config.report_interval
+        + contract.app_config.validate_report_interval
+        + 1
+}
+
+fn wallet(contract: &Dwork, account_id: ValidAccountId) -> Balance {
+    contract
+        .internal_get_account(account_id.as_ref())
+        .wallet_balance
+}
+
+fn task_input(price: Balance, max_participants: u16) -> TaskInput {
+    TaskInput {
+        title: "Landing page".to_string(),
+        description: "Design and build our landing page".to_string(),
+        price: price.into(),
+        max_participants,
+        duration: (7 * DAY).into(),
+        category_id: "design".to_string(),
+        milestones: None,
+        hiring: None,
+        invitees: None,
+        starts_at: None,
+        draft: None,
+        requirements: None,
+        reveal_interval: None,
+        token_id: None,
+    }
+}
+
+// Paid from the owner's wallet
+fn create_task(contract: &mut Dwork, input: TaskInput) -> TaskId {
+    set_context(owner(), 0, START);
+    contract.new_task(input)
+}
+
+fn new_task(contract: &mut Dwork, price: Balance, max_participants: u16) -> TaskId {
+    create_task(contract, task_input(price, max_participants))
+}
+
+fn new_token_task(contract: &mut Dwork, price: Balance, max_participants: u16) -> TaskId {
+    let input = TaskInput {
+        token_id: Some(token().into()),
+        ..task_input(price, max_participants)
+    };
+    set_context(token(), 0, START);
+    contract.ft_on_transfer(
+        owner().into(),
+        U128(10 * price),
+        json!({ "NewTask": { "task": input } }).to_string(),
+    );
+    contract
+        .task_recores
+        .keys_as_vector()
+        .iter()
+        .last()
+        .unwrap()
+}
+
+// 1 NEAR for the design, due in 3 days, then 2 NEAR for the page, due in 7 days
+fn new_milestone_task(contract: &mut Dwork) -> TaskId {
+    let milestones = vec![
+        MilestoneInput {
+            deliverable: "Figma design".to_string(),
+            amount: NEAR.into(),
+            duration: (3 * DAY).into(),
+        },
+        MilestoneInput {
+            deliverable: "Deployed page".to_string(),
+            amount: (2 * NEAR).into(),
+            duration: (7 * DAY).into(),
+        },
+    ];
+    create_task(
+        contract,
+        TaskInput {
+            milestones: Some(milestones),
+            ..task_input(3 * NEAR, 1)
+        },
+    )
+}
+
+fn new_hiring_task(contract: &mut Dwork, max_participants: u16) -> TaskId {
+    create_task(
+        contract,
+        TaskInput {
+            hiring: Some(true),
+            ..task_input(NEAR, max_participants)
+        },
+    )
+}
+
+fn new_invite_only_task(contract: &mut Dwork) -> TaskId {
+    create_task(
+        contract,
+        TaskInput {
+            invitees: Some(vec![worker().into()]),
+            ..task_input(NEAR, 1)
+        },
+    )
+}
+
+fn new_draft_task(contract: &mut Dwork) -> TaskId {
+    create_task(
+        contract,
+        TaskInput {
+            draft: Some(true),
+            ..task_input(NEAR, 1)
+        },
+    )
+}
+
+fn new_task_with_requirements(contract: &mut Dwork, requirements: TaskRequirements) -> TaskId {
+    create_task(
+        contract,
+        TaskInput {
+            requirements: Some(requirements),
+            ..task_input(NEAR, 1)
+        },
+    )
+}
+
+fn no_requirements() -> TaskRequirements {
+    TaskRequirements {
+        min_pos_point: None,
+        max_neg_point_ratio: None,
+        min_completed_jobs: None,
+        min_account_age: None,
+    }
+}
+
+// Reveals are accepted for 2 days after the 7 days of submissions
+fn new_commit_reveal_task(contract: &mut Dwork) -> TaskId {
+    create_task(
+        contract,
+        TaskInput {
+            reveal_interval: Some((2 * DAY).into()),
+            ..task_input(NEAR, 1)
+        },
+    )
+}
+
+fn commitment(proof: &[Deliverable], salt: &str) -> Base64VecU8 {
+    let mut message = near_sdk::serde_json::to_vec(proof).unwrap();
+    message.extend_from_slice(salt.as_bytes());
+    env::sha256(&message).into()
+}
+
+fn commit(contract: &mut Dwork, task_id: &TaskId, worker_id: ValidAccountId) {
+    let hash = commitment(&proof("https://github.com/dwork/landing"), "salt");
+    set_context(worker_id, SUBMIT_BOND, START + 1);
+    contract.commit_work(task_id.clone(), hash);
+}
+
+fn apply(contract: &mut Dwork, task_id: &TaskId, worker_id: ValidAccountId) {
+    set_context(worker_id, SUBMIT_BOND, START + 1);
+    contract.apply(task_id.clone(), "I built dwork.io".to_string(), 20);
+}
+
+fn proof(url: &str) -> Vec<Deliverable> {
+    vec![Deliverable::Url {
+        url: url.to_string(),
+    }]
+}
+
+fn encrypted(recipient_public_key: Vec<u8>) -> Vec<Deliverable> {
+    vec![Deliverable::Encrypted {
+        ciphertext: vec![7; 64].into(),
+        nonce: vec![1; 24].into(),
+        sender_public_key: vec![2; 32].into(),
+        recipient_public_key: recipient_public_key.into(),
+    }]
+}
+
+fn submit_milestone(
+    contract: &mut Dwork,
+    task_id: &TaskId,
+    worker_id: ValidAccountId,
+    milestone: u8,
+) {
+    let bond = if milestone == 0 { SUBMIT_BOND } else { 0 };
+    set_context(worker_id, bond, START + 1);
+    contract.submit_work(
+        task_id.clone(),
+        proof("https://figma.com/file/landing"),
+        Some(milestone),
+    );
+}
+
+fn submit(contract: &mut Dwork, task_id: &TaskId, worker_id: ValidAccountId) {
+    set_context(worker_id, SUBMIT_BOND, START + 1);
+    contract.submit_work(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing"),
+        None,
+    );
+}
+
+fn approve(contract: &mut Dwork, task_id: &TaskId, worker_id: ValidAccountId) {
+    set_context(owner(), 0, START + 2);
+    contract.approve_work(task_id.clone(), worker_id.into(), None);
+}
+
+fn reject(contract: &mut Dwork, task_id: &TaskId, worker_id: ValidAccountId) {
+    set_context(owner(), 0, START + 2);
+    contract.reject_work(
+        task_id.clone(),
+        worker_id.into(),
+        "Off brief".to_string(),
+        None,
+    );
+}
+
+fn report(contract: &mut Dwork, task_id: &TaskId, worker_id: ValidAccountId) -> ReportId {
+    set_context(worker_id.clone(), 0, START + 3);
+    contract.report_rejection(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing/pull/1"),
+        None,
+    );
+    format!("{}_{}", worker_id, task_id)
+}
+
+fn request_changes(contract: &mut Dwork, task_id: &TaskId, worker_id: ValidAccountId) {
+    set_context(owner(), 0, START + 2);
+    contract.request_changes(
+        task_id.clone(),
+        worker_id.into(),
+        "Use our brand colors".to_string(),
+    );
+}
+
+fn proposal_status(
+    contract: &Dwork,
+    task_id: &TaskId,
+    worker_id: ValidAccountId,
+) -> ProposalStatus {
+    contract
+        .internal_get_proposal(task_id.clone(), worker_id.into())
+        .1
+        .status
+}
+
+// Receiver, method name and arguments of the function calls created by the last call
+fn created_function_calls() -> Vec<(String, String, Value)> {
+    get_created_receipts()
+        .iter()
+        .flat_map(|receipt| {
+            let receipt: Value =
+                serde_json::from_str(&serde_json::to_string(receipt).unwrap()).unwrap();
+            let receiver_id = receipt["receiver_id"].as_str().unwrap().to_string();
+            receipt["actions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(|action| action.get("FunctionCall"))
+                .map(|call| {
+                    (
+                        receiver_id.clone(),
+                        call["method_name"].as_str().unwrap().to_string(),
+                        serde_json::from_str(call["args"].as_str().unwrap()).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn created_transfers_to(receiver_id: ValidAccountId) -> usize {
+    get_created_receipts()
+        .iter()
+        .map(|receipt| {
+            serde_json::from_str::<Value>(&serde_json::to_string(receipt).unwrap()).unwrap()
+        })
+        .filter(|receipt| receipt["receiver_id"] == receiver_id.as_ref().as_str())
+        .flat_map(|receipt| receipt["actions"].as_array().unwrap().clone())
+        .filter(|action| action.get("Transfer").is_some())
+        .count()
+}
+
+#[test]
+fn cancel_task_refunds_the_budget_not_owed_and_pending_bonds() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 2);
+    submit(&mut contract, &task_id, worker());
+    submit(&mut contract, &task_id, other_worker());
+    approve(&mut contract, &task_id, worker());
+
+    let owner_before = wallet(&contract, owner());
+    let other_worker_before = wallet(&contract, other_worker());
+    set_context(owner(), 0, START + 3);
+    contract.cancel_task(task_id.clone());
+
+    // Budget is 2 NEAR, the approved seat stays owed and the fee is not refunded
+    assert_eq!(wallet(&contract, owner()), owner_before + NEAR);
+    assert_eq!(
+        wallet(&contract, other_worker()),
+        other_worker_before + SUBMIT_BOND
+    );
+    assert_eq!(
+        proposal_status(&contract, &task_id, other_worker()),
+        ProposalStatus::Cancelled
+    );
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Approved
+    );
+
+    let task = contract.internal_get_task(&task_id);
+    assert_eq!(task.status, TaskStatus::Cancelled);
+    assert_eq!(task.buget, NEAR);
+}
+
+#[test]
+#[should_panic(expected = "Task is closed")]
+fn submit_work_on_a_cancelled_task_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    set_context(owner(), 0, START);
+    contract.cancel_task(task_id.clone());
+
+    submit(&mut contract, &task_id, worker());
+}
+
+#[test]
+#[should_panic(expected = "Only owner can cancel this task")]
+fn cancel_task_by_another_account_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(worker(), 0, START);
+    contract.cancel_task(task_id);
+}
+
+#[test]
+#[should_panic(expected = "Task still in progress")]
+fn cancel_task_waits_until_rejections_can_no_longer_be_reported() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 3);
+    contract.cancel_task(task_id);
+}
+
+#[test]
+#[should_panic(expected = "Task is already closed")]
+fn mark_task_as_completed_after_cancel_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    set_context(owner(), 0, START);
+    contract.cancel_task(task_id.clone());
+
+    contract.mark_task_as_completed(task_id);
+}
+
+#[test]
+fn report_rejection_within_report_interval() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 3);
+    contract.report_rejection(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing/pull/1"),
+        None,
+    );
+
+    let report_id = format!("{}_{}", worker(), task_id);
+    assert!(contract.reports.get(&report_id).is_some());
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Rejected {
+            reason: "Off brief".to_string(),
+            reject_at: START + 2,
+            report_id: Some(report_id),
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "Not available to report this rejection")]
+fn report_rejection_after_report_interval_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+
+    let report_interval = contract.app_config.report_interval;
+    set_context(worker(), 0, START + 3 + report_interval);
+    contract.report_rejection(
+        task_id,
+        proof("https://github.com/dwork/landing/pull/1"),
+        None,
+    );
+}
+
+#[test]
+fn update_task_records_amendments_and_tops_up_the_budget() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    let owner_before = wallet(&contract, owner());
+    set_context(owner(), 0, START + 1);
+    contract.update_task(
+        task_id.clone(),
+        Some("Design, build and host our landing page".to_string()),
+        Some(DAY.into()),
+        Some((2 * NEAR).into()),
+        Some(2),
+    );
+
+    // 2 seats at 2 NEAR plus the 10% fee, 1.1 NEAR was paid when the task was created
+    assert_eq!(wallet(&contract, owner()), owner_before - 33 * NEAR / 10);
+    let task = contract.internal_get_task(&task_id);
+    assert_eq!(task.buget, 4 * NEAR);
+    assert_eq!(task.fee, 4 * NEAR / 10);
+    assert_eq!(task.submit_available_until, START + 8 * DAY);
+    assert_eq!(contract.task_by_id(task_id.clone()).version, 1);
+
+    let amendments = contract.task_amendments(task_id, 0, 10);
+    assert_eq!(amendments.len(), 1);
+    assert_eq!(amendments[0].amended_at.0, START + 1);
+    assert_eq!(
+        amendments[0].description.as_deref(),
+        Some("Design, build and host our landing page")
+    );
+    assert_eq!(
+        amendments[0].submit_available_until.map(|item| item.0),
+        Some(START + 8 * DAY)
+    );
+    assert_eq!(amendments[0].price.map(|item| item.0), Some(2 * NEAR));
+    // Balances and timestamps are strings in JSON
+    let json = serde_json::to_value(&amendments[0]).unwrap();
+    assert_eq!(json["price"], (2 * NEAR).to_string());
+    assert_eq!(json["amended_at"], (START + 1).to_string());
+    assert_eq!(amendments[0].max_participants, Some(2));
+}
+
+#[test]
+#[should_panic(expected = "Cannot change price after approving a proposal")]
+fn update_task_price_after_an_approval_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 2);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 3);
+    contract.update_task(task_id, None, None, Some((2 * NEAR).into()), None);
+}
+
+#[test]
+fn update_task_adds_seats_after_an_approval() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 3);
+    contract.update_task(task_id.clone(), None, None, None, Some(2));
+
+    submit(&mut contract, &task_id, other_worker());
+    approve(&mut contract, &task_id, other_worker());
+    assert_eq!(contract.internal_get_task(&task_id).buget, 2 * NEAR);
+}
+
+#[test]
+#[should_panic(expected = "Only owner can update this task")]
+fn update_task_by_another_account_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(worker(), 0, START + 1);
+    contract.update_task(task_id, None, Some(DAY.into()), None, None);
+}
+
+#[test]
+fn milestone_task_pays_each_approved_milestone() {
+    let mut contract = setup();
+    let task_id = new_milestone_task(&mut contract);
+    let worker_before = wallet(&contract, worker());
+
+    submit_milestone(&mut contract, &task_id, worker(), 0);
+    set_context(owner(), 0, START + 2);
+    contract.approve_work(task_id.clone(), worker().into(), Some(0));
+    set_context(worker(), 0, START + 3);
+    contract.claim(task_id.clone(), Some(0));
+    assert_eq!(wallet(&contract, worker()), worker_before + NEAR);
+
+    submit_milestone(&mut contract, &task_id, worker(), 1);
+    set_context(owner(), 0, START + 2);
+    contract.approve_work(task_id.clone(), worker().into(), Some(1));
+    set_context(worker(), 0, START + 3);
+    contract.claim(task_id.clone(), Some(1));
+    assert_eq!(wallet(&contract, worker()), worker_before + 3 * NEAR);
+
+    let (_, proposal) = contract.internal_get_proposal(task_id, worker().into());
+    assert_eq!(proposal.status, ProposalStatus::Approved);
+    assert_eq!(proposal.milestones.len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "Previous milestone has not been approved")]
+fn submit_milestone_before_the_previous_one_is_approved_panics() {
+    let mut contract = setup();
+    let task_id = new_milestone_task(&mut contract);
+    submit_milestone(&mut contract, &task_id, worker(), 0);
+
+    submit_milestone(&mut contract, &task_id, worker(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Milestone amounts must add up to the price")]
+fn new_task_with_milestones_not_adding_up_to_the_price_panics() {
+    let mut contract = setup();
+
+    create_task(
+        &mut contract,
+        TaskInput {
+            milestones: Some(vec![MilestoneInput {
+                deliverable: "Figma design".to_string(),
+                amount: NEAR.into(),
+                duration: (3 * DAY).into(),
+            }]),
+            ..task_input(3 * NEAR, 1)
+        },
+    );
+}
+
+#[test]
+fn cancel_milestone_task_refunds_the_milestones_not_approved() {
+    let mut contract = setup();
+    let task_id = new_milestone_task(&mut contract);
+    submit_milestone(&mut contract, &task_id, worker(), 0);
+    set_context(owner(), 0, START + 2);
+    contract.approve_work(task_id.clone(), worker().into(), Some(0));
+    submit_milestone(&mut contract, &task_id, worker(), 1);
+
+    let owner_before = wallet(&contract, owner());
+    set_context(owner(), 0, START + 3);
+    contract.cancel_task(task_id.clone());
+
+    // Budget is 3 NEAR, only the first milestone is owed
+    assert_eq!(wallet(&contract, owner()), owner_before + 2 * NEAR);
+    let (_, proposal) = contract.internal_get_proposal(task_id, worker().into());
+    assert_eq!(proposal.milestones[1].status, ProposalStatus::Cancelled);
+}
+
+#[test]
+fn approve_report_of_a_milestone_locks_its_amount() {
+    let mut contract = setup();
+    let task_id = new_milestone_task(&mut contract);
+    submit_milestone(&mut contract, &task_id, worker(), 0);
+    set_context(owner(), 0, START + 2);
+    contract.reject_work(
+        task_id.clone(),
+        worker().into(),
+        "Off brief".to_string(),
+        Some(0),
+    );
+    set_context(worker(), 0, START + 3);
+    contract.report_rejection(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing/pull/1"),
+        Some(0),
+    );
+
+    set_context(admin(), 0, START + 4);
+    contract.approve_report(format!("{}_{}#0", worker(), task_id));
+
+    let locked_balance = contract
+        .internal_get_account(worker().as_ref())
+        .locked_balance
+        .get(&format!("{}#0", task_id))
+        .unwrap();
+    assert_eq!(locked_balance.amount, NEAR);
+    let (_, proposal) = contract.internal_get_proposal(task_id, worker().into());
+    assert_eq!(proposal.status, ProposalStatus::Approved);
+    assert_eq!(
+        proposal.milestones[0].status,
+        ProposalStatus::ApprovedByAdmin {
+            account_id: admin().into()
+        }
+    );
+}
+
+#[test]
+fn hire_closes_hiring_and_refunds_the_other_applicants() {
+    let mut contract = setup();
+    let task_id = new_hiring_task(&mut contract, 1);
+    apply(&mut contract, &task_id, worker());
+    apply(&mut contract, &task_id, other_worker());
+
+    let other_worker_before = wallet(&contract, other_worker());
+    set_context(owner(), 0, START + 2);
+    contract.hire(task_id.clone(), vec![worker().into()]);
+
+    assert_eq!(
+        contract.internal_get_task(&task_id).hiring,
+        Some(HiringStatus::Closed)
+    );
+    assert_eq!(
+        wallet(&contract, other_worker()),
+        other_worker_before + SUBMIT_BOND
+    );
+    let (_, application) = contract
+        .internal_get_application(task_id.clone(), other_worker().into())
+        .unwrap();
+    assert_eq!(application.status, ApplicationStatus::Declined);
+    assert_eq!(application.bond, 0);
+
+    // The bond was paid with the application
+    set_context(worker(), 0, START + 3);
+    contract.submit_work(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing"),
+        None,
+    );
+    approve(&mut contract, &task_id, worker());
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Approved
+    );
+}
+
+#[test]
+#[should_panic(expected = "Only hired workers can submit to this task")]
+fn submit_work_to_a_hiring_task_without_being_hired_panics() {
+    let mut contract = setup();
+    let task_id = new_hiring_task(&mut contract, 1);
+    apply(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 2);
+    contract.submit_work(task_id, proof("https://github.com/dwork/landing"), None);
+}
+
+#[test]
+#[should_panic(expected = "Only owner can close hiring before the deadline")]
+fn close_hiring_by_another_account_before_the_deadline_panics() {
+    let mut contract = setup();
+    let task_id = new_hiring_task(&mut contract, 1);
+
+    set_context(worker(), 0, START + 1);
+    contract.close_hiring(task_id);
+}
+
+#[test]
+fn cancel_hiring_task_refunds_all_application_bonds() {
+    let mut contract = setup();
+    let task_id = new_hiring_task(&mut contract, 2);
+    apply(&mut contract, &task_id, worker());
+    apply(&mut contract, &task_id, other_worker());
+    set_context(owner(), 0, START + 2);
+    contract.hire(task_id.clone(), vec![worker().into()]);
+
+    let worker_before = wallet(&contract, worker());
+    let other_worker_before = wallet(&contract, other_worker());
+    set_context(owner(), 0, START + 3);
+    contract.cancel_task(task_id.clone());
+
+    assert_eq!(wallet(&contract, worker()), worker_before + SUBMIT_BOND);
+    assert_eq!(
+        wallet(&contract, other_worker()),
+        other_worker_before + SUBMIT_BOND
+    );
+    assert!(!contract
+        .internal_get_account(worker().as_ref())
+        .current_jobs
+        .contains(&task_id));
+}
+
+#[test]
+fn invite_only_task_accepts_invited_workers() {
+    let mut contract = setup();
+    let task_id = new_invite_only_task(&mut contract);
+
+    submit(&mut contract, &task_id, worker());
+
+    assert!(contract.task_by_id(task_id.clone()).invite_only);
+    assert_eq!(
+        contract.task_invitees(task_id.clone(), 0, 10),
+        vec![worker().to_string()]
+    );
+    let invited_tasks = contract.invited_tasks(worker().into(), 0, 10);
+    assert_eq!(invited_tasks.len(), 1);
+    assert_eq!(invited_tasks[0].0, task_id);
+}
+
+#[test]
+#[should_panic(expected = "This task is invite only")]
+fn submit_work_to_an_invite_only_task_without_an_invite_panics() {
+    let mut contract = setup();
+    let task_id = new_invite_only_task(&mut contract);
+
+    submit(&mut contract, &task_id, other_worker());
+}
+
+#[test]
+#[should_panic(expected = "This task is invite only")]
+fn submit_work_after_the_invite_is_removed_panics() {
+    let mut contract = setup();
+    let task_id = new_invite_only_task(&mut contract);
+    set_context(owner(), 0, START);
+    contract.remove_invitees(task_id.clone(), vec![worker().into()]);
+
+    assert!(contract
+        .internal_get_account(worker().as_ref())
+        .invited_tasks
+        .is_empty());
+    submit(&mut contract, &task_id, worker());
+}
+
+#[test]
+fn add_invitees_lets_new_workers_submit() {
+    let mut contract = setup();
+    let task_id = new_invite_only_task(&mut contract);
+    set_context(owner(), 0, START);
+    contract.add_invitees(task_id.clone(), vec![other_worker().into()]);
+
+    submit(&mut contract, &task_id, other_worker());
+    assert_eq!(
+        proposal_status(&contract, &task_id, other_worker()),
+        ProposalStatus::Pending
+    );
+}
+
+#[test]
+fn finalize_task_pays_the_bounty_and_refunds_the_owner() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 2);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+
+    let owner_before = wallet(&contract, owner());
+    let finalizer_before = wallet(&contract, finalizer());
+    set_context(finalizer(), 0, finalize_at(&contract));
+    contract.finalize_task(task_id.clone());
+
+    let bounty = contract.app_config.finalize_bounty;
+    assert_eq!(wallet(&contract, finalizer()), finalizer_before + bounty);
+    assert_eq!(wallet(&contract, owner()), owner_before + NEAR - bounty);
+    assert_eq!(
+        contract.internal_get_task(&task_id).status,
+        TaskStatus::Completed
+    );
+}
+
+#[test]
+fn finalize_task_by_the_owner_pays_no_bounty() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    let owner_before = wallet(&contract, owner());
+    set_context(owner(), 0, finalize_at(&contract));
+    contract.finalize_task(task_id);
+
+    assert_eq!(wallet(&contract, owner()), owner_before + NEAR);
+}
+
+#[test]
+#[should_panic(expected = "Task can't be finalized yet")]
+fn finalize_task_before_the_report_windows_pass_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(finalizer(), 0, START + 7 * DAY + 1);
+    contract.finalize_task(task_id);
+}
+
+#[test]
+#[should_panic(expected = "Just admin can change config")]
+fn change_config_by_another_account_panics() {
+    let mut contract = setup();
+
+    set_context(owner(), 0, START);
+    contract.change_config(None, None, None, None, Some(NEAR), None);
+}
+
+#[test]
+fn spawn_task_from_a_template_is_paid_by_the_owner() {
+    let mut contract = setup();
+    set_context(owner(), 0, START);
+    let template_id = contract.new_template(task_input(NEAR, 1), None);
+
+    let owner_before = wallet(&contract, owner());
+    let task_id = contract.spawn_task(template_id);
+
+    assert_eq!(wallet(&contract, owner()), owner_before - 11 * NEAR / 10);
+    assert_eq!(
+        contract.internal_get_task(&task_id).owner,
+        owner().to_string()
+    );
+    let template = contract.template_by_id(template_id);
+    assert_eq!(template.last_task_id, Some(task_id));
+    assert_eq!(template.last_spawned_at, Some(START));
+    assert_eq!(contract.templates_by_owner(owner().into(), 0, 10).len(), 1);
+}
+
+#[test]
+fn spawn_task_from_a_recurring_template_by_anyone() {
+    let mut contract = setup();
+    set_context(owner(), 0, START);
+    let template_id = contract.new_template(task_input(NEAR, 1), Some(DAY.into()));
+    let first_task_id = contract.spawn_task(template_id);
+    contract.cancel_task(first_task_id.clone());
+
+    set_context(finalizer(), 0, START + DAY);
+    let task_id = contract.spawn_task(template_id);
+
+    assert_ne!(task_id, first_task_id);
+    assert_eq!(
+        contract.internal_get_task(&task_id).owner,
+        owner().to_string()
+    );
+}
+
+#[test]
+#[should_panic(expected = "Last task is still open")]
+fn spawn_task_while_the_last_one_is_open_panics() {
+    let mut contract = setup();
+    set_context(owner(), 0, START);
+    let template_id = contract.new_template(task_input(NEAR, 1), Some(DAY.into()));
+    contract.spawn_task(template_id);
+
+    set_context(finalizer(), 0, START + DAY);
+    contract.spawn_task(template_id);
+}
+
+#[test]
+#[should_panic(expected = "Only owner can spawn a task from this template")]
+fn spawn_task_from_a_template_of_another_account_panics() {
+    let mut contract = setup();
+    set_context(owner(), 0, START);
+    let template_id = contract.new_template(task_input(NEAR, 1), None);
+
+    set_context(finalizer(), 0, START);
+    contract.spawn_task(template_id);
+}
+
+#[test]
+fn new_tasks_creates_every_task_in_one_call() {
+    let mut contract = setup();
+
+    let owner_before = wallet(&contract, owner());
+    set_context(owner(), 0, START);
+    let task_ids = contract.new_tasks(vec![task_input(NEAR, 1), task_input(2 * NEAR, 1)]);
+
+    assert_eq!(
+        task_ids,
+        vec![format!("{}_0", owner()), format!("{}_1", owner())]
+    );
+    assert_eq!(wallet(&contract, owner()), owner_before - 33 * NEAR / 10);
+    assert_eq!(contract.internal_get_task(&task_ids[1]).price, 2 * NEAR);
+}
+
+#[test]
+#[should_panic(expected = "Balance not available to pay")]
+fn new_tasks_over_the_available_balance_creates_none() {
+    let mut contract = setup();
+
+    set_context(owner(), 0, START);
+    contract.new_tasks(vec![task_input(4 * NEAR, 1); 3]);
+}
+
+#[test]
+#[should_panic(expected = "Task is not published yet")]
+fn submit_work_to_a_draft_panics() {
+    let mut contract = setup();
+    let task_id = new_draft_task(&mut contract);
+
+    assert!(contract.available_tasks(0, 10).is_empty());
+    submit(&mut contract, &task_id, worker());
+}
+
+#[test]
+fn publish_task_keeps_the_duration_of_a_late_draft() {
+    let mut contract = setup();
+    let task_id = new_draft_task(&mut contract);
+
+    set_context(owner(), 0, START + DAY);
+    contract.publish_task(task_id.clone());
+
+    let task = contract.internal_get_task(&task_id);
+    assert_eq!(task.status, TaskStatus::Open);
+    assert_eq!(task.starts_at, START + DAY);
+    assert_eq!(task.submit_available_until, START + 8 * DAY);
+    assert_eq!(contract.available_tasks(0, 10).len(), 1);
+}
+
+#[test]
+fn discard_draft_refunds_the_budget() {
+    let mut contract = setup();
+    let owner_before = wallet(&contract, owner());
+    let task_id = new_draft_task(&mut contract);
+
+    set_context(owner(), 0, START + 1);
+    contract.discard_draft(task_id.clone());
+
+    assert_eq!(wallet(&contract, owner()), owner_before);
+    assert!(contract.task_recores.get(&task_id).is_none());
+}
+
+#[test]
+#[should_panic(expected = "Task is not open for submissions yet")]
+fn submit_work_before_a_scheduled_task_starts_panics() {
+    let mut contract = setup();
+    let task_id = create_task(
+        &mut contract,
+        TaskInput {
+            starts_at: Some((START + DAY).into()),
+            ..task_input(NEAR, 1)
+        },
+    );
+
+    submit(&mut contract, &task_id, worker());
+}
+
+#[test]
+fn claim_records_the_completed_job_and_earnings() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 3);
+    contract.claim(task_id.clone(), None);
+
+    let account = contract.internal_get_account(worker().as_ref());
+    assert_eq!(account.total_earn, NEAR);
+    assert!(account.completed_jobs.contains(&task_id));
+    assert!(!account.current_jobs.contains(&task_id));
+}
+
+#[test]
+fn submit_work_when_the_worker_meets_the_requirements() {
+    let mut contract = setup();
+    let first_task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &first_task_id, worker());
+    approve(&mut contract, &first_task_id, worker());
+    set_context(worker(), 0, START + 3);
+    contract.claim(first_task_id, None);
+
+    let task_id = new_task_with_requirements(
+        &mut contract,
+        TaskRequirements {
+            min_pos_point: Some(10),
+            max_neg_point_ratio: Some(0),
+            min_completed_jobs: Some(1),
+            ..no_requirements()
+        },
+    );
+    submit(&mut contract, &task_id, worker());
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Pending
+    );
+}
+
+#[test]
+#[should_panic(expected = "Requires at least 10 positive points")]
+fn submit_work_without_enough_positive_points_panics() {
+    let mut contract = setup();
+    let task_id = new_task_with_requirements(
+        &mut contract,
+        TaskRequirements {
+            min_pos_point: Some(10),
+            ..no_requirements()
+        },
+    );
+
+    submit(&mut contract, &task_id, worker());
+}
+
+#[test]
+#[should_panic(expected = "Account must be registered for at least")]
+fn submit_work_from_a_new_account_panics() {
+    let mut contract = setup();
+    let task_id = new_task_with_requirements(
+        &mut contract,
+        TaskRequirements {
+            min_account_age: Some((2 * DAY).into()),
+            ..no_requirements()
+        },
+    );
+
+    submit(&mut contract, &task_id, worker());
+}
+
+#[test]
+fn withdraw_proposal_refunds_the_submit_bond() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    let worker_before = wallet(&contract, worker());
+    set_context(worker(), 0, START + 2);
+    contract.withdraw_proposal(task_id.clone());
+
+    assert_eq!(wallet(&contract, worker()), worker_before + SUBMIT_BOND);
+    assert!(contract.internal_get_task(&task_id).proposals.is_empty());
+    assert!(!contract
+        .internal_get_account(worker().as_ref())
+        .current_jobs
+        .contains(&task_id));
+
+    // The worker can submit again
+    submit(&mut contract, &task_id, worker());
+}
+
+#[test]
+#[should_panic(expected = "Only pending proposal can be withdrawn")]
+fn withdraw_proposal_after_approval_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 3);
+    contract.withdraw_proposal(task_id);
+}
+
+#[test]
+fn resubmit_work_keeps_the_previous_proof() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    request_changes(&mut contract, &task_id, worker());
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::ChangesRequested {
+            note: "Use our brand colors".to_string()
+        }
+    );
+
+    set_context(worker(), 0, START + 3);
+    contract.resubmit_work(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing/tree/v2"),
+    );
+
+    let (_, proposal) = contract.internal_get_proposal(task_id.clone(), worker().into());
+    assert_eq!(proposal.status, ProposalStatus::Pending);
+    assert_eq!(proposal.submit_time, START + 3);
+    assert_eq!(proposal.revisions.len(), 1);
+    assert_eq!(proposal.revisions[0].submit_time, START + 1);
+    assert_eq!(
+        proposal.revisions[0].proof_of_work,
+        proof("https://github.com/dwork/landing")
+    );
+
+    approve(&mut contract, &task_id, worker());
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Approved
+    );
+}
+
+#[test]
+#[should_panic(expected = "Owner has not requested changes")]
+fn resubmit_work_without_a_change_request_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 3);
+    contract.resubmit_work(task_id, proof("https://github.com/dwork/landing"));
+}
+
+#[test]
+#[should_panic(expected = "Reached 3 revision rounds")]
+fn request_changes_after_the_maximum_revision_rounds_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    for _ in 0..3 {
+        request_changes(&mut contract, &task_id, worker());
+        set_context(worker(), 0, START + 3);
+        contract.resubmit_work(task_id.clone(), proof("https://github.com/dwork/landing"));
+    }
+
+    request_changes(&mut contract, &task_id, worker());
+}
+
+#[test]
+fn cancel_task_refunds_the_bond_of_a_proposal_waiting_for_changes() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    request_changes(&mut contract, &task_id, worker());
+
+    let worker_before = wallet(&contract, worker());
+    set_context(owner(), 0, START + 3);
+    contract.cancel_task(task_id.clone());
+
+    assert_eq!(wallet(&contract, worker()), worker_before + SUBMIT_BOND);
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Cancelled
+    );
+}
+
+#[test]
+fn mark_task_as_completed_refunds_the_submit_bonds() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    submit(&mut contract, &task_id, other_worker());
+    approve(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, other_worker());
+
+    let worker_before = wallet(&contract, worker());
+    let other_worker_before = wallet(&contract, other_worker());
+    set_context(owner(), 0, START + 6 * DAY);
+    contract.mark_task_as_completed(task_id.clone());
+
+    assert_eq!(wallet(&contract, worker()), worker_before + SUBMIT_BOND);
+    assert_eq!(
+        wallet(&contract, other_worker()),
+        other_worker_before + SUBMIT_BOND
+    );
+    let (_, proposal) = contract.internal_get_proposal(task_id, other_worker().into());
+    assert_eq!(proposal.bond.status, BondStatus::Refunded);
+}
+
+#[test]
+fn bond_is_forfeited_when_the_report_is_rejected() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+    let report_id = report(&mut contract, &task_id, worker());
+
+    set_context(admin(), 0, START + 4);
+    contract.reject_report(report_id);
+
+    let worker_before = wallet(&contract, worker());
+    set_context(owner(), 0, START + 6 * DAY);
+    contract.mark_task_as_completed(task_id.clone());
+
+    let (_, proposal) = contract.internal_get_proposal(task_id, worker().into());
+    assert_eq!(proposal.bond.status, BondStatus::Forfeited);
+    assert_eq!(wallet(&contract, worker()), worker_before);
+}
+
+#[test]
+fn withdraw_proposal_of_a_hired_worker_keeps_the_bond_in_the_application() {
+    let mut contract = setup();
+    let task_id = new_hiring_task(&mut contract, 1);
+    apply(&mut contract, &task_id, worker());
+    set_context(owner(), 0, START + 2);
+    contract.hire(task_id.clone(), vec![worker().into()]);
+    set_context(worker(), 0, START + 3);
+    contract.submit_work(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing"),
+        None,
+    );
+
+    let worker_before = wallet(&contract, worker());
+    contract.withdraw_proposal(task_id.clone());
+
+    assert_eq!(wallet(&contract, worker()), worker_before);
+    let (_, application) = contract
+        .internal_get_application(task_id, worker().into())
+        .unwrap();
+    assert_eq!(application.bond, SUBMIT_BOND);
+}
+
+#[test]
+fn auto_approve_a_proposal_the_owner_ignored() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    let review_interval = contract.app_config.review_interval;
+    set_context(finalizer(), 0, START + 1 + review_interval);
+    contract.auto_approve(task_id.clone(), worker().into(), None);
+
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Approved
+    );
+    let worker_account = contract.internal_get_account(worker().as_ref());
+    assert_eq!(
+        worker_account.locked_balance.get(&task_id).unwrap().amount,
+        NEAR
+    );
+    assert_eq!(
+        contract.internal_get_account(owner().as_ref()).neg_point,
+        contract.app_config.med_minus as u32
+    );
+}
+
+#[test]
+#[should_panic(expected = "Owner can still review this proposal")]
+fn auto_approve_within_the_review_interval_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    set_context(finalizer(), 0, START + DAY);
+    contract.auto_approve(task_id, worker().into(), None);
+}
+
+#[test]
+#[should_panic(expected = "You have approved for 1 participants")]
+fn auto_approve_when_all_seats_are_taken_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    submit(&mut contract, &task_id, other_worker());
+    approve(&mut contract, &task_id, worker());
+
+    let review_interval = contract.app_config.review_interval;
+    set_context(finalizer(), 0, START + 1 + review_interval);
+    contract.auto_approve(task_id, other_worker().into(), None);
+}
+
+#[test]
+#[should_panic(expected = "Url must start with http:// or https://")]
+fn submit_work_with_an_invalid_url_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(worker(), SUBMIT_BOND, START + 1);
+    contract.submit_work(task_id, proof("github.com/dwork/landing"), None);
+}
+
+#[test]
+#[should_panic(expected = "Submit at least one deliverable")]
+fn submit_work_without_deliverables_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(worker(), SUBMIT_BOND, START + 1);
+    contract.submit_work(task_id, vec![], None);
+}
+
+#[test]
+fn submit_work_with_typed_deliverables() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    let deliverables = vec![
+        Deliverable::GitCommit {
+            repo: "https://github.com/dwork/landing".to_string(),
+            sha: "a94a8fe5ccb19ba61c4c0873d391e987982fbbd3".to_string(),
+        },
+        Deliverable::Ipfs {
+            cid: "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string(),
+        },
+        Deliverable::ContentHash {
+            sha256: "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string(),
+        },
+    ];
+
+    set_context(worker(), SUBMIT_BOND, START + 1);
+    contract.submit_work(task_id.clone(), deliverables.clone(), None);
+
+    let (_, proposal) = contract.internal_get_proposal(task_id, worker().into());
+    assert_eq!(proposal.proof_of_work, deliverables);
+}
+
+#[test]
+#[should_panic(expected = "Invalid commit sha")]
+fn submit_work_with_an_invalid_commit_sha_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(worker(), SUBMIT_BOND, START + 1);
+    contract.submit_work(
+        task_id,
+        vec![Deliverable::GitCommit {
+            repo: "https://github.com/dwork/landing".to_string(),
+            sha: "main".to_string(),
+        }],
+        None,
+    );
+}
+
+#[test]
+fn reveal_work_matching_the_commitment() {
+    let mut contract = setup();
+    let task_id = new_commit_reveal_task(&mut contract);
+    commit(&mut contract, &task_id, worker());
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Committed
+    );
+
+    set_context(worker(), 0, START + 7 * DAY + 1);
+    contract.reveal_work(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing"),
+        "salt".to_string(),
+    );
+
+    let (_, proposal) = contract.internal_get_proposal(task_id.clone(), worker().into());
+    assert_eq!(proposal.status, ProposalStatus::Pending);
+    assert_eq!(
+        proposal.proof_of_work,
+        proof("https://github.com/dwork/landing")
+    );
+
+    set_context(owner(), 0, START + 7 * DAY + 2);
+    contract.approve_work(task_id.clone(), worker().into(), None);
+    assert_eq!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Approved
+    );
+}
+
+#[test]
+#[should_panic(expected = "Cannot report this reject reason")]
+fn report_a_mismatched_reveal_panics() {
+    let mut contract = setup();
+    let task_id = new_commit_reveal_task(&mut contract);
+    commit(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 7 * DAY + 1);
+    contract.reveal_work(
+        task_id.clone(),
+        proof("https://github.com/dwork/other"),
+        "salt".to_string(),
+    );
+    assert!(matches!(
+        proposal_status(&contract, &task_id, worker()),
+        ProposalStatus::Rejected { reason, .. } if reason == "mismatch"
+    ));
+
+    contract.report_rejection(
+        task_id,
+        proof("https://github.com/dwork/landing/pull/1"),
+        None,
+    );
+}
+
+#[test]
+#[should_panic(expected = "Reveal is not available until the submission deadline")]
+fn reveal_work_before_the_deadline_panics() {
+    let mut contract = setup();
+    let task_id = new_commit_reveal_task(&mut contract);
+    commit(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 2);
+    contract.reveal_work(
+        task_id,
+        proof("https://github.com/dwork/landing"),
+        "salt".to_string(),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Work is not revealed yet")]
+fn approve_work_before_the_reveal_panics() {
+    let mut contract = setup();
+    let task_id = new_commit_reveal_task(&mut contract);
+    commit(&mut contract, &task_id, worker());
+
+    approve(&mut contract, &task_id, worker());
+}
+
+#[test]
+#[should_panic(expected = "Commit a hash of your work first")]
+fn submit_work_to_a_commit_reveal_task_panics() {
+    let mut contract = setup();
+    let task_id = new_commit_reveal_task(&mut contract);
+
+    submit(&mut contract, &task_id, worker());
+}
+
+#[test]
+fn unrevealed_commitment_forfeits_the_bond() {
+    let mut contract = setup();
+    let task_id = new_commit_reveal_task(&mut contract);
+    commit(&mut contract, &task_id, worker());
+
+    let worker_before = wallet(&contract, worker());
+    set_context(owner(), 0, START + 9 * DAY + 1);
+    contract.mark_task_as_completed(task_id.clone());
+
+    let (_, proposal) = contract.internal_get_proposal(task_id, worker().into());
+    assert_eq!(proposal.bond.status, BondStatus::Forfeited);
+    assert_eq!(wallet(&contract, worker()), worker_before);
+}
+
+#[test]
+fn submit_work_encrypted_for_the_owner() {
+    let mut contract = setup();
+    set_context(owner(), 0, START);
+    contract.set_encryption_key(Some(vec![3; 32].into()));
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(worker(), SUBMIT_BOND, START + 1);
+    contract.submit_work(task_id.clone(), encrypted(vec![3; 32]), None);
+
+    let (_, proposal) = contract.internal_get_proposal(task_id, worker().into());
+    assert_eq!(proposal.proof_of_work, encrypted(vec![3; 32]));
+}
+
+#[test]
+#[should_panic(expected = "Task owner has no encryption key")]
+fn submit_work_encrypted_without_an_owner_key_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(worker(), SUBMIT_BOND, START + 1);
+    contract.submit_work(task_id, encrypted(vec![3; 32]), None);
+}
+
+#[test]
+#[should_panic(expected = "Encrypted for an outdated key of the task owner")]
+fn submit_work_encrypted_for_a_rotated_key_panics() {
+    let mut contract = setup();
+    set_context(owner(), 0, START);
+    contract.set_encryption_key(Some(vec![3; 32].into()));
+    contract.set_encryption_key(Some(vec![4; 32].into()));
+    let task_id = new_task(&mut contract, NEAR, 1);
+
+    set_context(worker(), SUBMIT_BOND, START + 1);
+    contract.submit_work(task_id, encrypted(vec![3; 32]), None);
+}
+
+#[test]
+#[should_panic(expected = "Encrypted deliverables are not allowed here")]
+fn report_rejection_with_an_encrypted_deliverable_panics() {
+    let mut contract = setup();
+    set_context(owner(), 0, START);
+    contract.set_encryption_key(Some(vec![3; 32].into()));
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 3);
+    contract.report_rejection(task_id, encrypted(vec![3; 32]), None);
+}
+
+#[test]
+fn post_message_lists_newest_first() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 2);
+    contract.post_message(
+        task_id.clone(),
+        worker().into(),
+        "Can you add a footer?".into(),
+    );
+    set_context(worker(), 0, START + 3);
+    contract.post_message(task_id.clone(), worker().into(), "Done".into());
+
+    let messages = contract.messages_by_proposal(task_id, worker().into(), 0, 10);
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].body, "Done");
+    assert_eq!(messages[0].author, worker().to_string());
+    assert_eq!(messages[1].author, owner().to_string());
+}
+
+#[test]
+#[should_panic(expected = "Only owner, worker or admins handling a report can post")]
+fn post_message_by_an_admin_without_a_report_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    set_context(admin(), 0, START + 2);
+    contract.post_message(task_id, worker().into(), "Hello".into());
+}
+
+#[test]
+fn post_message_by_an_admin_on_a_reported_proposal() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+    report(&mut contract, &task_id, worker());
+
+    set_context(admin(), 0, START + 4);
+    contract.post_message(task_id.clone(), worker().into(), "Looking into it".into());
+
+    let messages = contract.messages_by_proposal(task_id, worker().into(), 0, 10);
+    assert_eq!(messages[0].author, admin().to_string());
+}
+
+#[test]
+#[should_panic(expected = "Message too long")]
+fn post_message_over_the_maximum_length_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    set_context(worker(), 0, START + 2);
+    contract.post_message(task_id, worker().into(), "a".repeat(2001));
+}
+
+#[test]
+fn proposals_by_task_filters_by_status() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 2);
+    submit(&mut contract, &task_id, worker());
+    submit(&mut contract, &task_id, other_worker());
+    approve(&mut contract, &task_id, worker());
+
+    let all = contract.proposals_by_task(task_id.clone(), None, 0, 10);
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].account_id, worker().to_string());
+
+    let pending =
+        contract.proposals_by_task(task_id.clone(), Some(ProposalStatusKind::Pending), 0, 10);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].account_id, other_worker().to_string());
+
+    let second_page = contract.proposals_by_task(task_id.clone(), None, 1, 10);
+    assert_eq!(second_page[0].account_id, other_worker().to_string());
+
+    let task = contract.task_by_id(task_id);
+    assert_eq!(
+        task.proposal_counts.get(&ProposalStatusKind::Approved),
+        Some(&1)
+    );
+    assert_eq!(
+        task.proposal_counts.get(&ProposalStatusKind::Pending),
+        Some(&1)
+    );
+}
+
+#[test]
+fn proposals_by_worker_shows_locked_payouts() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+
+    let proposals = contract.proposals_by_worker(worker().into(), None, 0, 10);
+    assert_eq!(proposals.len(), 1);
+    assert_eq!(proposals[0].title, "Landing page");
+    assert_eq!(proposals[0].proposal.task_id, task_id);
+    assert_eq!(proposals[0].locked.0, NEAR);
+    assert_eq!(proposals[0].claimable.0, 0);
+
+    let rejected =
+        contract.proposals_by_worker(worker().into(), Some(ProposalStatusKind::Rejected), 0, 10);
+    assert!(rejected.is_empty());
+}
+
+#[test]
+fn ft_on_transfer_without_message_deposits_tokens() {
+    let mut contract = setup();
+
+    set_context(token(), 0, START);
+    let unused = contract.ft_on_transfer(owner().into(), U128(500), String::new());
+
+    assert!(matches!(unused, PromiseOrValue::Value(U128(0))));
+    assert_eq!(
+        contract.token_balance_of(owner().into(), token().into()).0,
+        500
+    );
+}
+
+#[test]
+fn ft_on_transfer_creates_a_task_paid_with_the_token() {
+    let mut contract = setup();
+
+    let task_id = new_token_task(&mut contract, 100, 2);
+
+    let task = contract.internal_get_task(&task_id);
+    assert_eq!(task.token_id, Some(token().into()));
+    assert_eq!(task.buget, 200);
+    assert_eq!(task.fee, 20);
+    assert_eq!(
+        contract.treasury_token_balances.get(token().as_ref()),
+        Some(20)
+    );
+    assert_eq!(contract.treasury_balance, 0);
+    // Excess of the transfer stays in the token balance of the owner
+    assert_eq!(
+        contract.token_balance_of(owner().into(), token().into()).0,
+        1000 - 220
+    );
+}
+
+#[test]
+fn ft_on_transfer_tops_up_a_task() {
+    let mut contract = setup();
+    let task_id = new_token_task(&mut contract, 100, 2);
+
+    set_context(token(), 0, START);
+    contract.ft_on_transfer(
+        owner().into(),
+        U128(50),
+        json!({ "TopUp": { "task_id": task_id } }).to_string(),
+    );
+
+    assert_eq!(contract.internal_get_task(&task_id).buget, 250);
+    assert_eq!(
+        contract.token_balance_of(owner().into(), token().into()).0,
+        780
+    );
+}
+
+#[test]
+#[should_panic(expected = "Invalid message")]
+fn ft_on_transfer_rejects_an_invalid_message() {
+    let mut contract = setup();
+
+    set_context(token(), 0, START);
+    contract.ft_on_transfer(owner().into(), U128(100), "{\"Unknown\": {}}".to_string());
+}
+
+#[test]
+#[should_panic(expected = "Token is not whitelisted")]
+fn ft_on_transfer_rejects_other_tokens() {
+    let mut contract = setup();
+
+    set_context(other_worker(), 0, START);
+    contract.ft_on_transfer(owner().into(), U128(100), String::new());
+}
+
+#[test]
+fn claim_of_a_token_task_calls_ft_transfer() {
+    let mut contract = setup();
+    let task_id = new_token_task(&mut contract, 100, 1);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+    let near_balance = wallet(&contract, worker());
+
+    set_context(worker(), 0, START + 3);
+    contract.claim(task_id, None);
+
+    let calls = created_function_calls();
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].0, token().as_ref().as_str());
+    assert_eq!(calls[0].1, "ft_transfer");
+    assert_eq!(calls[0].2["receiver_id"], worker().as_ref().as_str());
+    assert_eq!(calls[0].2["amount"], "100");
+    assert_eq!(calls[1].0, contract_id().as_ref().as_str());
+    assert_eq!(calls[1].1, "on_ft_transfer");
+    // Payment is in the token, the NEAR balance is not credited
+    assert_eq!(wallet(&contract, worker()), near_balance);
+}
+
+#[test]
+fn failed_ft_transfer_is_kept_in_the_token_balance() {
+    let mut contract = setup();
+
+    set_callback_context(PromiseResult::Failed);
+    assert!(!contract.on_ft_transfer(worker().into(), token().into(), U128(100)));
+    assert_eq!(
+        contract.token_balance_of(worker().into(), token().into()).0,
+        100
+    );
+
+    set_callback_context(PromiseResult::Successful(Vec::new()));
+    assert!(contract.on_ft_transfer(worker().into(), token().into(), U128(100)));
+    assert_eq!(
+        contract.token_balance_of(worker().into(), token().into()).0,
+        100
+    );
+}
+
+#[test]
+fn new_task_paid_with_the_attached_deposit() {
+    let mut contract = setup();
+    let before = wallet(&contract, owner());
+    let collateral_before = contract
+        .storage_accounts
+        .get(owner().as_ref())
+        .unwrap()
+        .balance;
+    let input = task_input(NEAR, 1);
+
+    set_context(owner(), 2 * NEAR, START);
+    let task_id = contract.new_task(input);
+
+    let task = contract.internal_get_task(&task_id);
+    assert_eq!(task.buget, NEAR);
+    assert_eq!(task.fee, NEAR / 10);
+    // Storage is added to the collateral, the excess of the deposit stays in the owner's wallet
+    let storage_cost = contract
+        .storage_accounts
+        .get(owner().as_ref())
+        .unwrap()
+        .balance
+        - collateral_before;
+    assert!(storage_cost > 0);
+    assert_eq!(
+        wallet(&contract, owner()),
+        before + 2 * NEAR - NEAR - NEAR / 10 - storage_cost
+    );
+}
+
+#[test]
+fn new_task_collects_the_fee_in_the_treasury() {
+    let mut contract = setup();
+    let before = wallet(&contract, owner());
+
+    let task_id = new_task(&mut contract, NEAR, 2);
+
+    assert_eq!(contract.internal_get_task(&task_id).fee, 2 * NEAR / 10);
+    assert_eq!(contract.treasury_balance, 2 * NEAR / 10);
+    assert_eq!(wallet(&contract, owner()), before - 22 * NEAR / 10);
+}
+
+#[test]
+fn withdraw_fees_sends_the_collected_fees_to_the_treasury() {
+    let mut contract = setup();
+    new_task(&mut contract, NEAR, 1);
+
+    set_context(admin(), 0, START + 1);
+    contract.withdraw_fees(None, None);
+
+    assert_eq!(contract.treasury_balance, 0);
+    assert_eq!(created_transfers_to(treasury()), 1);
+}
+
+#[test]
+#[should_panic(expected = "Fee rate must be at most 10000 basis points")]
+fn set_fee_config_over_the_maximum_rate_panics() {
+    let mut contract = setup();
+
+    set_context(admin(), 0, START);
+    contract.set_fee_config(Some(10_001), None);
+}
+
+#[test]
+#[should_panic(expected = "Just admin can withdraw fees")]
+fn withdraw_fees_by_another_account_panics() {
+    let mut contract = setup();
+    new_task(&mut contract, NEAR, 1);
+
+    set_context(owner(), 0, START + 1);
+    contract.withdraw_fees(None, None);
+}
+
+#[test]
+fn withdraw_sends_the_wallet_balance() {
+    let mut contract = setup();
+
+    set_context(worker(), 1, START);
+    contract.withdraw(Some(U128(4 * NEAR)));
+
+    assert_eq!(wallet(&contract, worker()), 6 * NEAR);
+    assert_eq!(created_transfers_to(worker()), 1);
+}
+
+#[test]
+fn failed_withdraw_restores_the_wallet_balance() {
+    let mut contract = setup();
+    set_context(worker(), 1, START);
+    contract.withdraw(None);
+    assert_eq!(wallet(&contract, worker()), 0);
+
+    set_callback_context(PromiseResult::Failed);
+    assert!(!contract.on_transferd(worker().into(), U128(10 * NEAR)));
+    assert_eq!(wallet(&contract, worker()), 10 * NEAR);
+}
+
+#[test]
+#[should_panic(expected = "Wallet balance not available")]
+fn withdraw_more_than_the_wallet_balance_panics() {
+    let mut contract = setup();
+
+    set_context(worker(), 1, START);
+    contract.withdraw(Some(U128(11 * NEAR)));
+}
+
+#[test]
+fn tip_is_paid_from_the_owner_wallet_and_recorded() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+    let owner_before = wallet(&contract, owner());
+
+    set_context(owner(), 0, START + 3);
+    contract.tip(task_id.clone(), worker().into(), NEAR.into(), None);
+    set_context(worker(), 0, START + 4);
+    contract.claim(task_id.clone(), None);
+
+    assert_eq!(wallet(&contract, owner()), owner_before - NEAR);
+    assert_eq!(wallet(&contract, worker()), 12 * NEAR);
+    let earnings = contract.earnings_by_worker(worker().into(), 0, 10);
+    assert_eq!(earnings.len(), 2);
+    assert!(matches!(earnings[0].kind, EarningKind::Payment));
+    assert!(matches!(earnings[1].kind, EarningKind::Tip));
+    assert_eq!(earnings[1].task_id, task_id);
+    assert_eq!(
+        contract.internal_get_account(worker().as_ref()).total_earn,
+        2 * NEAR
+    );
+}
+
+#[test]
+fn tip_from_budget_is_limited_to_the_unowed_buget() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 2);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 3);
+    contract.tip(
+        task_id.clone(),
+        worker().into(),
+        (NEAR / 2).into(),
+        Some(true),
+    );
+
+    assert_eq!(
+        contract.internal_get_task(&task_id).buget,
+        2 * NEAR - NEAR / 2
+    );
+    assert_eq!(wallet(&contract, worker()), 10 * NEAR + NEAR / 2);
+}
+
+#[test]
+#[should_panic(expected = "Tip is larger than the remaining buget")]
+fn tip_from_budget_over_the_unowed_buget_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 3);
+    contract.tip(task_id, worker().into(), 1.into(), Some(true));
+}
+
+#[test]
+#[should_panic(expected = "Only approved workers can be tipped")]
+fn tip_to_a_pending_worker_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 3);
+    contract.tip(task_id, worker().into(), NEAR.into(), None);
+}
+
+#[test]
+#[should_panic(expected = "Task is already closed")]
+fn withdraw_proposal_after_the_bonds_are_settled_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 2);
+    contract.mark_task_as_completed(task_id.clone());
+    assert_eq!(wallet(&contract, worker()), 10 * NEAR + SUBMIT_BOND);
+
+    set_context(worker(), 0, START + 3);
+    contract.withdraw_proposal(task_id);
+}
+
+#[test]
+#[should_panic(expected = "All 1 seats are already taken")]
+fn approve_report_of_a_milestone_with_no_free_seat_panics() {
+    let mut contract = setup();
+    let task_id = new_milestone_task(&mut contract);
+    submit_milestone(&mut contract, &task_id, worker(), 0);
+    submit_milestone(&mut contract, &task_id, other_worker(), 0);
+    set_context(owner(), 0, START + 2);
+    contract.reject_work(
+        task_id.clone(),
+        worker().into(),
+        "Off brief".to_string(),
+        Some(0),
+    );
+    contract.approve_work(task_id.clone(), other_worker().into(), Some(0));
+    set_context(worker(), 0, START + 3);
+    contract.report_rejection(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing/pull/1"),
+        Some(0),
+    );
+
+    set_context(admin(), 0, START + 4);
+    contract.approve_report(format!("{}_{}#0", worker(), task_id));
+}
+
+#[test]
+fn claim_of_the_first_milestone_keeps_the_job_current() {
+    let mut contract = setup();
+    let task_id = new_milestone_task(&mut contract);
+    submit_milestone(&mut contract, &task_id, worker(), 0);
+    set_context(owner(), 0, START + 2);
+    contract.approve_work(task_id.clone(), worker().into(), Some(0));
+
+    set_context(worker(), 0, START + 3);
+    contract.claim(task_id.clone(), Some(0));
+
+    let account = contract.internal_get_account(worker().as_ref());
+    assert!(account.current_jobs.contains(&task_id));
+    assert!(!account.completed_jobs.contains(&task_id));
+}
+
+#[test]
+#[should_panic(expected = "Note too long")]
+fn request_changes_with_a_note_over_the_message_length_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 2);
+    contract.request_changes(task_id, worker().into(), "a".repeat(2001));
+}
+
+#[test]
+#[should_panic(expected = "Can't extend the deadline after commitments start to be revealed")]
+fn update_task_extending_a_commit_reveal_task_after_its_deadline_panics() {
+    let mut contract = setup();
+    let task_id = new_commit_reveal_task(&mut contract);
+    commit(&mut contract, &task_id, worker());
+
+    set_context(owner(), 0, START + 7 * DAY + 1);
+    contract.update_task(task_id, None, Some(DAY.into()), None, None);
+}
+
+#[test]
+fn proposal_counts_follow_every_status_change() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 2);
+    submit(&mut contract, &task_id, worker());
+    submit(&mut contract, &task_id, other_worker());
+    request_changes(&mut contract, &task_id, worker());
+
+    let task = contract.internal_get_task(&task_id);
+    assert_eq!(task.proposal_count(ProposalStatusKind::Pending), 1);
+    assert_eq!(task.proposal_count(ProposalStatusKind::ChangesRequested), 1);
+
+    set_context(worker(), 0, START + 3);
+    contract.resubmit_work(
+        task_id.clone(),
+        proof("https://github.com/dwork/landing/tree/v2"),
+    );
+    set_context(owner(), 0, START + 4);
+    contract.approve_work(task_id.clone(), worker().into(), None);
+    set_context(other_worker(), 0, START + 4);
+    contract.withdraw_proposal(task_id.clone());
+
+    let task = contract.internal_get_task(&task_id);
+    assert_eq!(task.proposal_count(ProposalStatusKind::Approved), 1);
+    assert_eq!(task.proposal_counts.len(), 1);
+}
+
+#[test]
+fn reject_report_counts_the_admin_rejection() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+    let report_id = report(&mut contract, &task_id, worker());
+
+    set_context(admin(), 0, START + 4);
+    contract.reject_report(report_id);
+
+    let task = contract.internal_get_task(&task_id);
+    assert_eq!(task.proposal_count(ProposalStatusKind::RejectedByAdmin), 1);
+    assert_eq!(task.proposal_count(ProposalStatusKind::Rejected), 0);
+}
+
+#[test]
+fn finalize_bounty_is_not_paid_for_token_tasks() {
+    let mut contract = setup();
+    let task_id = new_token_task(&mut contract, 100, 1);
+
+    set_context(finalizer(), 0, finalize_at(&contract));
+    contract.finalize_task(task_id.clone());
+
+    assert_eq!(
+        contract.internal_get_task(&task_id).status,
+        TaskStatus::Completed
+    );
+    assert_eq!(wallet(&contract, finalizer()), 10 * NEAR);
+    assert!(created_function_calls()
+        .iter()
+        .all(|(_, _, args)| args["receiver_id"] != finalizer().as_ref().as_str()));
+}
+
+#[test]
+#[should_panic(expected = "Attach at least")]
+fn new_task_with_a_short_deposit_panics() {
+    let mut contract = setup();
+
+    set_context(owner(), NEAR, START);
+    contract.new_task(task_input(NEAR, 1));
+}
+
+#[test]
+fn draft_fee_is_collected_on_publish() {
+    let mut contract = setup();
+    let task_id = new_draft_task(&mut contract);
+    assert_eq!(contract.treasury_balance, 0);
+
+    set_context(owner(), 0, START);
+    contract.publish_task(task_id);
+    assert_eq!(contract.treasury_balance, NEAR / 10);
+}
+
+#[test]
+fn discard_draft_refunds_the_fee_after_fees_are_withdrawn() {
+    let mut contract = setup();
+    let draft_id = new_draft_task(&mut contract);
+    new_task(&mut contract, NEAR, 1);
+
+    set_context(admin(), 0, START);
+    contract.withdraw_fees(None, None);
+    assert_eq!(contract.treasury_balance, 0);
+
+    set_context(owner(), 0, START);
+    contract.discard_draft(draft_id);
+
+    assert_eq!(contract.treasury_balance, 0);
+    assert_eq!(wallet(&contract, owner()), 10 * NEAR - NEAR - NEAR / 10);
+}
+
+#[test]
+fn failed_withdraw_fees_restores_the_treasury() {
+    let mut contract = setup();
+    new_task(&mut contract, NEAR, 1);
+
+    set_context(admin(), 0, START);
+    contract.withdraw_fees(None, None);
+    assert_eq!(contract.treasury_balance, 0);
+
+    set_callback_context(PromiseResult::Failed);
+    assert!(!contract.on_fees_withdrawn(None, U128(NEAR / 10)));
+    assert_eq!(contract.treasury_balance, NEAR / 10);
+}
+
+#[test]
+fn failed_withdraw_token_fees_restores_the_treasury() {
+    let mut contract = setup();
+    new_token_task(&mut contract, 100, 1);
+
+    set_context(admin(), 0, START);
+    contract.withdraw_fees(Some(token().into()), None);
+    assert_eq!(
+        contract.treasury_token_balances.get(token().as_ref()),
+        Some(0)
+    );
+
+    set_callback_context(PromiseResult::Failed);
+    assert!(!contract.on_fees_withdrawn(Some(token().into()), U128(10)));
+    assert_eq!(
+        contract.treasury_token_balances.get(token().as_ref()),
+        Some(10)
+    );
+    // Fees are not credited to the treasury account as a user
+    assert_eq!(
+        contract
+            .token_balance_of(treasury().into(), token().into())
+            .0,
+        0
+    );
+}
+
+#[test]
+fn tip_gives_the_point_bonus_once() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, worker());
+    let points = contract.internal_get_account(worker().as_ref()).pos_point;
+
+    set_context(owner(), 0, START + 3);
+    contract.tip(task_id.clone(), worker().into(), NEAR.into(), None);
+    contract.tip(task_id, worker().into(), 1.into(), None);
+
+    let account = contract.internal_get_account(worker().as_ref());
+    assert_eq!(account.wallet_balance, 11 * NEAR + 1);
+    assert_eq!(account.earnings.len(), 2);
+    assert_eq!(
+        account.pos_point,
+        points + contract.app_config.sml_plus as u32
+    );
+}
+
+#[test]
+#[should_panic(expected = "Task is already closed")]
+fn approve_work_after_completion_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    set_context(owner(), 0, START + 2);
+    contract.mark_task_as_completed(task_id.clone());
+
+    approve(&mut contract, &task_id, worker());
+}
+
+#[test]
+#[should_panic(expected = "Task is already closed")]
+fn reject_work_after_completion_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    set_context(owner(), 0, START + 2);
+    contract.mark_task_as_completed(task_id.clone());
+
+    reject(&mut contract, &task_id, worker());
+}
+
+#[test]
+#[should_panic(expected = "Task is already closed")]
+fn approve_report_of_a_closed_task_panics() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+    let report_id = report(&mut contract, &task_id, worker());
+    let mut task = contract.internal_get_task(&task_id);
+    task.status = TaskStatus::Cancelled;
+    contract.task_recores.insert(&task_id, &task);
+
+    set_context(admin(), 0, START + 4);
+    contract.approve_report(report_id);
+}
+
+#[test]
+fn approve_report_takes_the_seat_of_the_latest_approval() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    submit(&mut contract, &task_id, worker());
+    submit(&mut contract, &task_id, other_worker());
+    reject(&mut contract, &task_id, worker());
+    let report_id = report(&mut contract, &task_id, worker());
+    approve(&mut contract, &task_id, other_worker());
+
+    set_context(admin(), 0, START + 4);
+    contract.approve_report(report_id);
+
+    assert!(matches!(
+        proposal_status(&contract, &task_id, other_worker()),
+        ProposalStatus::Rejected { reason, .. } if reason == "late"
+    ));
+    assert!(contract
+        .internal_get_account(other_worker().as_ref())
+        .locked_balance
+        .get(&task_id)
+        .is_none());
+
+    let owner_before = wallet(&contract, owner());
+    set_context(owner(), 0, finalize_at(&contract));
+    contract.mark_task_as_completed(task_id.clone());
+    assert_eq!(wallet(&contract, owner()), owner_before);
+    assert_eq!(
+        contract.internal_get_task(&task_id).status,
+        TaskStatus::Completed
+    );
+}
+
+#[test]
+fn cancel_task_keeps_the_bond_refunded_to_the_owner() {
+    let mut contract = setup();
+    let task_id = new_task(&mut contract, NEAR, 1);
+    let owner_before = wallet(&contract, owner());
+    submit(&mut contract, &task_id, owner());
+
+    set_context(owner(), 0, START + 2);
+    contract.cancel_task(task_id);
+
+    assert_eq!(
+        wallet(&contract, owner()),
+        owner_before + NEAR + SUBMIT_BOND
+    );
+}
+
+#[test]
+fn mark_task_as_completed_keeps_the_remainder_paid_to_an_owner_handling_reports() {
+    let mut contract = setup();
+    set_context(contract_id(), 0, START);
+    contract.add_admin(owner().into());
+    let task_id = new_task(&mut contract, NEAR, 2);
+    submit(&mut contract, &task_id, worker());
+    reject(&mut contract, &task_id, worker());
+    let report_id = report(&mut contract, &task_id, worker());
+    set_context(owner(), 0, START + 4);
+    contract.approve_report(report_id);
+
+    let owner_before = wallet(&contract, owner());
+    set_context(owner(), 0, finalize_at(&contract));
+    contract.mark_task_as_completed(task_id);
+
+    // Unused seat goes to the admin who handled the report
+    assert_eq!(wallet(&contract, owner()), owner_before + NEAR);
+}
+
+#[test]
+fn proposals_by_worker_keeps_positions_after_a_withdrawal() {
+    let mut contract = setup();
+    let first = new_task(&mut contract, NEAR, 1);
+    let second = new_task(&mut contract, NEAR, 1);
+    let third = new_task(&mut contract, NEAR, 1);
+    for task_id in [&first, &second, &third] {
+        submit(&mut contract, task_id, worker());
+    }
+
+    set_context(worker(), 0, START + 2);
+    contract.withdraw_proposal(first.clone());
+    submit(&mut contract, &first, worker());
+
+    let page = contract.proposals_by_worker(worker().into(), None, 1, 2);
+    let task_ids: Vec<TaskId> = page.into_iter().map(|item| item.proposal.task_id).collect();
+    assert_eq!(task_ids, vec![second, third]);
+
+    // Withdrawn submission is skipped, the new one is listed once at the end
+    let all = contract.proposals_by_worker(worker().into(), None, 0, 10);
+    assert_eq!(all.len(), 3);
+    assert_eq!(all[2].proposal.task_id, first);
+}
+
+#[test]
+fn task_by_id_returns_wrapped_milestones() {
+    let mut contract = setup();
+    let task_id = new_milestone_task(&mut contract);
+
+    let task = contract.task_by_id(task_id);
+    assert_eq!(task.milestones.len(), 2);
+    assert_eq!(task.milestones[1].amount.0, 2 * NEAR);
+    assert_eq!(task.milestones[1].deadline.0, START + 7 * DAY);
+    // Balances and timestamps are strings in JSON
+    let json = serde_json::to_value(&task.milestones[0]).unwrap();
+    assert_eq!(json["amount"], NEAR.to_string());
+    assert_eq!(json["deadline"], (START + 3 * DAY).to_string());
+}
+
+#[test]
+fn mismatched_reveal_forfeits_the_bond() {
+    let mut contract = setup();
+    let task_id = new_commit_reveal_task(&mut contract);
+    commit(&mut contract, &task_id, worker());
+    set_context(worker(), 0, START + 7 * DAY + 1);
+    contract.reveal_work(
+        task_id.clone(),
+        proof("https://github.com/dwork/other"),
+        "salt".to_string(),
+    );
+
+    let worker_before = wallet(&contract, worker());
+    set_context(owner(), 0, finalize_at(&contract) + 2 * DAY);
+    contract.mark_task_as_completed(task_id.clone());
+
+    let (_, proposal) = contract.internal_get_proposal(task_id, worker().into());
+    assert_eq!(proposal.bond.status, BondStatus::Forfeited);
+    assert_eq!(wallet(&contract, worker()), worker_before);
+}
+
+#[test]
+fn spawn_task_after_the_last_draft_is_discarded() {
+    let mut contract = setup();
+    set_context(owner(), 0, START);
+    let input = TaskInput {
+        draft: Some(true),
+        ..task_input(NEAR, 1)
+    };
+    let template_id = contract.new_template(input, Some(DAY.into()));
+    let draft_id = contract.spawn_task(template_id);
+    contract.discard_draft(draft_id);
+
+    set_context(finalizer(), 0, START + DAY);
+    let task_id = contract.spawn_task(template_id);
+
+    assert_eq!(
+        contract.internal_get_task(&task_id).status,
+        TaskStatus::Draft
+    );
+}
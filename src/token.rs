@@ -0,0 +1,183 @@
+use crate::*;
+use near_sdk::json_types::U128;
+use near_sdk::PromiseOrValue;
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum FtMessage {
+    // Create a task paid with the transferred tokens
+    NewTask { task: Box<TaskInput> },
+    // Add the transferred tokens to the buget of a task
+    TopUp { task_id: TaskId },
+}
+
+#[near_bindgen]
+impl Dwork {
+    // An empty message only deposits the tokens to the sender's token balance
+    pub fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let token_id = env::predecessor_account_id();
+        assert!(
+            self.whitelisted_tokens.contains(&token_id),
+            "Token is not whitelisted"
+        );
+        let amount: Balance = amount.into();
+        let storage_update = self.new_storage_update(sender_id.clone());
+
+        self.internal_token_send(&sender_id, &token_id, amount);
+
+        if !msg.is_empty() {
+            match near_sdk::serde_json::from_str(&msg).expect("Invalid message") {
+                FtMessage::NewTask { mut task } => {
+                    task.token_id = Some(token_id);
+                    self.internal_create_task(&sender_id, *task);
+                }
+                FtMessage::TopUp { task_id } => {
+                    let mut task = self.internal_get_task(&task_id);
+                    assert_eq!(task.owner, sender_id, "Only owner can top up this task");
+                    assert!(
+                        matches!(task.status, TaskStatus::Draft | TaskStatus::Open),
+                        "Task is already closed"
+                    );
+                    assert!(
+                        task.token_id.as_ref() == Some(&token_id),
+                        "Task is not paid with this token"
+                    );
+
+                    self.internal_token_payment(&sender_id, &token_id, amount);
+                    task.buget += amount;
+                    self.task_recores.insert(&task_id, &task);
+                }
+            }
+        }
+
+        self.finalize_storage_update(storage_update);
+        PromiseOrValue::Value(U128(0))
+    }
+
+    // Withdraw deposited tokens or payouts which failed to transfer
+    #[payable]
+    pub fn withdraw_token(&mut self, token_id: AccountId, amount: Option<U128>) -> Promise {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let amount: Balance = amount
+            .map(|item| item.into())
+            .unwrap_or_else(|| self.internal_token_balance(&account_id, &token_id));
+        assert!(amount > 0, "Nothing to withdraw");
+
+        self.internal_token_payment(&account_id, &token_id, amount);
+        self.internal_ft_transfer(&account_id, &token_id, amount)
+    }
+
+    pub fn token_balance_of(&self, account_id: AccountId, token_id: AccountId) -> U128 {
+        self.internal_token_balance(&account_id, &token_id).into()
+    }
+
+    pub fn whitelisted_tokens(&self) -> Vec<AccountId> {
+        self.whitelisted_tokens.to_vec()
+    }
+}
+
+impl Dwork {
+    fn internal_gen_token_key(&self, account_id: &str, token_id: &str) -> String {
+        format!("{}:{}", account_id, token_id)
+    }
+
+    pub(crate) fn internal_token_balance(&self, account_id: &AccountId, token_id: &AccountId) -> Balance {
+        self.token_balances
+            .get(&self.internal_gen_token_key(account_id, token_id))
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn internal_token_payment(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &AccountId,
+        amount: Balance,
+    ) {
+        let balance = self.internal_token_balance(account_id, token_id);
+        assert!(
+            amount <= balance,
+            "Token balance not available to pay for internal request"
+        );
+
+        let key = self.internal_gen_token_key(account_id, token_id);
+        if balance == amount {
+            self.token_balances.remove(&key);
+        } else {
+            self.token_balances.insert(&key, &(balance - amount));
+        }
+    }
+
+    pub(crate) fn internal_token_send(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &AccountId,
+        amount: Balance,
+    ) {
+        let balance = self.internal_token_balance(account_id, token_id);
+        self.token_balances.insert(
+            &self.internal_gen_token_key(account_id, token_id),
+            &(balance + amount),
+        );
+    }
+
+    // Amount is restored to the token balance by the callback if the transfer failed
+    pub(crate) fn internal_ft_transfer(
+        &self,
+        account_id: &AccountId,
+        token_id: &AccountId,
+        amount: Balance,
+    ) -> Promise {
+        ext_ft::ft_transfer(
+            account_id.clone(),
+            U128(amount),
+            None,
+            token_id,
+            1,
+            GAS_FOR_FT_TRANSFER,
+        )
+        .then(ext_self::on_ft_transfer(
+            account_id.clone(),
+            token_id.clone(),
+            U128(amount),
+            &env::current_account_id(),
+            0,
+            DEFAULT_GAS_TO_PAY,
+        ))
+    }
+
+    // Pay from NEAR storage balance or from token balance
+    pub(crate) fn internal_charge(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &Option<AccountId>,
+        amount: Balance,
+    ) {
+        match token_id {
+            Some(token_id) => self.internal_token_payment(account_id, token_id, amount),
+            None => self.internal_payment(Some(account_id.clone()), amount),
+        }
+    }
+
+    // Send NEAR to storage balance or transfer tokens
+    pub(crate) fn internal_payout(
+        &mut self,
+        account_id: &AccountId,
+        token_id: &Option<AccountId>,
+        amount: Balance,
+    ) {
+        match token_id {
+            Some(token_id) => {
+                if amount > 0 {
+                    self.internal_ft_transfer(account_id, token_id, amount);
+                }
+            }
+            None => self.internal_send(Some(account_id.clone()), amount),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::*;
+use near_sdk::json_types::U128;
+
+const MAXIMUM_FEE_RATE_BPS: u16 = 10_000;
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TreasuryInfo {
+    pub treasury_id: Option<AccountId>,
+    pub fee_rate_bps: u16,
+    pub balance: U128,
+    pub token_balances: Vec<(AccountId, U128)>,
+}
+
+#[near_bindgen]
+impl Dwork {
+    pub fn set_fee_config(&mut self, fee_rate_bps: Option<u16>, treasury_id: Option<AccountId>) {
+        assert!(
+            self.is_admin(env::predecessor_account_id()),
+            "Just admin can change fee config"
+        );
+
+        if let Some(fee_rate_bps) = fee_rate_bps {
+            assert!(
+                fee_rate_bps <= MAXIMUM_FEE_RATE_BPS,
+                "Fee rate must be at most {} basis points",
+                MAXIMUM_FEE_RATE_BPS
+            );
+            self.app_config.fee_rate_bps = fee_rate_bps;
+        }
+
+        if let Some(treasury_id) = treasury_id {
+            self.app_config.treasury_id = Some(treasury_id);
+        }
+    }
+
+    // Collected fees which are not withdrawn yet
+    pub fn treasury_info(&self) -> TreasuryInfo {
+        TreasuryInfo {
+            treasury_id: self.app_config.treasury_id.clone(),
+            fee_rate_bps: self.app_config.fee_rate_bps,
+            balance: self.treasury_balance.into(),
+            token_balances: self
+                .treasury_token_balances
+                .iter()
+                .map(|(token_id, amount)| (token_id, amount.into()))
+                .collect(),
+        }
+    }
+
+    // Send collected fees in NEAR or in a token to the treasury account,
+    // fees are restored by on_fees_withdrawn if the transfer failed
+    pub fn withdraw_fees(&mut self, token_id: Option<AccountId>, amount: Option<U128>) -> Promise {
+        assert!(
+            self.is_admin(env::predecessor_account_id()),
+            "Just admin can withdraw fees"
+        );
+        let treasury_id = self
+            .app_config
+            .treasury_id
+            .clone()
+            .expect("Treasury account is not set");
+
+        let available = match &token_id {
+            Some(token_id) => self.treasury_token_balances.get(token_id).unwrap_or(0),
+            None => self.treasury_balance,
+        };
+        let amount: Balance = amount.map(|item| item.into()).unwrap_or(available);
+        assert!(amount > 0, "Nothing to withdraw");
+        assert!(amount <= available, "Not enough collected fees");
+
+        self.internal_remove_fee(&token_id, amount);
+        let transfer = match &token_id {
+            Some(token_id) => ext_ft::ft_transfer(
+                treasury_id,
+                U128(amount),
+                None,
+                token_id,
+                1,
+                GAS_FOR_FT_TRANSFER,
+            ),
+            None => Promise::new(treasury_id).transfer(amount),
+        };
+        transfer.then(ext_self::on_fees_withdrawn(
+            token_id,
+            amount.into(),
+            &env::current_account_id(),
+            0,
+            DEFAULT_GAS_TO_PAY,
+        ))
+    }
+}
+
+impl Dwork {
+    pub(crate) fn internal_task_fee(&self, amount: Balance) -> Balance {
+        amount
+            .checked_mul(self.app_config.fee_rate_bps.into())
+            .expect("Cannot calculate fee")
+            / Balance::from(MAXIMUM_FEE_RATE_BPS)
+    }
+
+    pub(crate) fn internal_collect_fee(&mut self, token_id: &Option<AccountId>, amount: Balance) {
+        match token_id {
+            Some(token_id) => {
+                let balance = self.treasury_token_balances.get(token_id).unwrap_or(0);
+                self.treasury_token_balances.insert(token_id, &(balance + amount));
+            }
+            None => self.treasury_balance += amount,
+        }
+    }
+
+    pub(crate) fn internal_remove_fee(&mut self, token_id: &Option<AccountId>, amount: Balance) {
+        match token_id {
+            Some(token_id) => {
+                let balance = self.treasury_token_balances.get(token_id).unwrap_or(0);
+                self.treasury_token_balances.insert(token_id, &(balance - amount));
+            }
+            None => self.treasury_balance -= amount,
+        }
+    }
+}
@@ -15,6 +15,19 @@ pub(crate) enum StorageKey {
     UserCompletedTasks { account_id: AccountId },
     // ProposalsPerTask { task_id: String },
     Admins,
+    TaskAmendments { task_id: TaskId },
+    Applications,
+    TaskInvitees { task_id: TaskId },
+    UserInvitedTasks { account_id: AccountId },
+    TaskTemplates,
+    UserTemplates { account_id: AccountId },
+    Messages,
+    ProposalMessages { proposal_id: ProposalId },
+    UserProposals { account_id: AccountId },
+    UserEarnings { account_id: AccountId },
+    WhitelistedTokens,
+    TokenBalances,
+    TreasuryTokenBalances,
 }
 
 pub fn assert_one_yocto() {
@@ -32,11 +45,7 @@ pub fn calculate_rev_limit(len: u64, from_index: u64, limit: u64) -> Range<u64>
         0
     };
 
-    let to = if len > from_index {
-        len - from_index
-    } else {
-        0
-    };
+    let to = len.saturating_sub(from_index);
 
     from..to
 }
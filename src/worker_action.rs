@@ -2,79 +2,286 @@ use crate::*;
 
 #[near_bindgen]
 impl Dwork {
+    // Milestone tasks: the first submission must be milestone 0 and creates the proposal,
+    // the next milestones can be submitted once the previous one is approved
     #[payable]
-    pub fn submit_work(&mut self, task_id: String, proof: String) {
-        // TODO: Allow user to use current balance
+    pub fn submit_work(
+        &mut self,
+        task_id: String,
+        proof: Vec<Deliverable>,
+        milestone: Option<u8>,
+    ) {
+        let task = self.internal_get_task(&task_id);
+        let worker_id = env::predecessor_account_id();
+        let proposal_id = self.internal_gen_proposal_id(task_id.clone(), worker_id.clone());
+        let storage_update = self.new_storage_update(worker_id.clone());
+
+        assert!(task.status != TaskStatus::Draft, "Task is not published yet");
+        assert!(task.status == TaskStatus::Open, "Task is closed");
         assert!(
-            env::attached_deposit() == self.app_config.submit_bond,
-            "Send exactly {:?} Near to submit",
-            self.app_config.submit_bond
+            task.reveal_interval.is_none(),
+            "Commit a hash of your work first"
         );
+        self.internal_assert_deliverables(&proof, Some(&task.owner));
+
+        if let (Some(index), Some(proposal)) = (milestone, self.proposals.get(&proposal_id)) {
+            self.internal_submit_milestone(&task, &proposal_id, proposal, index, proof);
+            self.finalize_storage_update(storage_update);
+            return;
+        }
+
+        self.internal_new_proposal(task_id, task, worker_id, proof, milestone, None);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Hash is sha256 of the json serialized proof followed by the salt
+    #[payable]
+    pub fn commit_work(&mut self, task_id: TaskId, hash: Base64VecU8) {
+        let task = self.internal_get_task(&task_id);
+        let worker_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(worker_id.clone());
+
+        assert!(task.status != TaskStatus::Draft, "Task is not published yet");
+        assert!(task.status == TaskStatus::Open, "Task is closed");
+        assert!(
+            task.reveal_interval.is_some(),
+            "This task doesn't use commit-reveal submissions"
+        );
+        assert_eq!(hash.0.len(), 32, "Hash must be a sha256");
+
+        self.internal_new_proposal(task_id, task, worker_id, Vec::new(), None, Some(hash));
+
+        self.finalize_storage_update(storage_update);
+    }
 
+    // Reveal is only available after the submission deadline, a mismatch rejects the proposal
+    pub fn reveal_work(&mut self, task_id: TaskId, proof: Vec<Deliverable>, salt: String) {
         let mut task = self.internal_get_task(&task_id);
         let worker_id = env::predecessor_account_id();
         let now = env::block_timestamp();
-        let proposal_id = self.internal_gen_proposal_id(task_id.clone(), worker_id.clone());
+        let storage_update = self.new_storage_update(worker_id.clone());
 
-        assert!(task.submit_available_until > now, "Request is expired");
+        assert!(task.status == TaskStatus::Open, "Task is closed");
+        let reveal_interval = task
+            .reveal_interval
+            .expect("This task doesn't use commit-reveal submissions");
+        assert!(
+            task.submit_available_until <= now,
+            "Reveal is not available until the submission deadline"
+        );
+        assert!(
+            now <= task.submit_available_until + reveal_interval,
+            "Reveal window is over"
+        );
+        self.internal_assert_deliverables(&proof, Some(&task.owner));
 
-        assert!(self.proposals.get(&proposal_id).is_none(), "Already submitted this task");
+        let (proposal_id, mut proposal) = self.internal_get_proposal(task_id.clone(), worker_id);
+        assert!(
+            proposal.status == ProposalStatus::Committed,
+            "Work is already revealed"
+        );
+
+        let mut message = near_sdk::serde_json::to_vec(&proof).expect("Failed to serialize proof");
+        message.extend_from_slice(salt.as_bytes());
+        let commitment = proposal.commitment.as_ref().expect("Commitment not found");
+
+        if env::sha256(&message) == commitment.0 {
+            proposal.submit_time = now;
+            proposal.proof_of_work = proof;
+            proposal.status = ProposalStatus::Pending;
+        } else {
+            proposal.status = ProposalStatus::Rejected {
+                reason: "mismatch".to_string(),
+                reject_at: now,
+                report_id: None,
+            };
+        }
+        self.internal_set_proposal(&mut task, &proposal_id, &proposal);
+        self.task_recores.insert(&task_id, &task);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    pub fn resubmit_work(&mut self, task_id: TaskId, proof: Vec<Deliverable>) {
+        let worker_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(worker_id.clone());
+
+        let mut task = self.internal_get_task(&task_id);
+        assert!(task.status == TaskStatus::Open, "Task is closed");
+        self.internal_assert_deliverables(&proof, Some(&task.owner));
 
+        let (proposal_id, mut proposal) = self.internal_get_proposal(task_id.clone(), worker_id);
         assert!(
-            task.proposals
-                .iter()
-                .filter(
-                    |v| self.proposals.get(v).expect("Proposal not found").status
-                        == ProposalStatus::Approved
-                )
-                .count()
-                < task.max_participants as usize,
-            "Full approved participants"
+            matches!(proposal.status, ProposalStatus::ChangesRequested { note: _ }),
+            "Owner has not requested changes"
         );
 
-        // Increase worker current task
+        proposal.revisions.push(ProofRevision {
+            submit_time: proposal.submit_time,
+            proof_of_work: proposal.proof_of_work,
+        });
+        proposal.submit_time = env::block_timestamp();
+        proposal.proof_of_work = proof;
+        proposal.status = ProposalStatus::Pending;
+        self.internal_set_proposal(&mut task, &proposal_id, &proposal);
+        self.task_recores.insert(&task_id, &task);
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    // Submit bond is refunded, hired workers keep their bond in the application
+    pub fn withdraw_proposal(&mut self, task_id: TaskId) {
+        let worker_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(worker_id.clone());
+
+        let mut task = self.internal_get_task(&task_id);
+        let (proposal_id, mut proposal) =
+            self.internal_get_proposal(task_id.clone(), worker_id.clone());
+
+        assert!(task.status == TaskStatus::Open, "Task is already closed");
+        assert!(
+            proposal.all_statuses().all(|status| matches!(
+                status,
+                ProposalStatus::Pending | ProposalStatus::Committed
+            )),
+            "Only pending proposal can be withdrawn"
+        );
+        // Bond was already settled or forfeited
+        assert!(
+            proposal.bond.status == BondStatus::Locked,
+            "Bond is not locked anymore"
+        );
+        proposal.bond.status = BondStatus::Refunded;
+
+        self.proposals.remove(&proposal_id);
+        task.proposals.retain(|item| *item != proposal_id);
+        task.remove_proposal_count(proposal.status.kind());
+        self.task_recores.insert(&task_id, &task);
+
         let mut worker = self.internal_get_account(&worker_id);
+        worker.proposals.replace(proposal.worker_index, &None);
+        if task.hiring.is_none() {
+            worker.current_jobs.remove(&task_id);
+        }
+        self.internal_set_account(&worker_id, worker);
+
+        if task.hiring.is_none() {
+            self.internal_send(None, proposal.bond.amount);
+        } else {
+            // Keep the bond for the next submission
+            let (application_id, mut application) = self
+                .internal_get_application(task_id, worker_id)
+                .expect("Application not found");
+            application.bond += proposal.bond.amount;
+            self.applications.insert(&application_id, &application);
+        }
+
+        self.finalize_storage_update(storage_update);
+    }
+
+    #[payable]
+    pub fn apply(&mut self, task_id: TaskId, cover_letter: String, estimated_hours: u32) {
+        assert!(
+            env::attached_deposit() == self.app_config.submit_bond,
+            "Send exactly {:?} Near to apply",
+            self.app_config.submit_bond
+        );
+
+        let worker_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(worker_id.clone());
+
+        let mut task = self.internal_get_task(&task_id);
+        let application_id = self.internal_gen_proposal_id(task_id.clone(), worker_id.clone());
+
+        assert!(task.status != TaskStatus::Draft, "Task is not published yet");
+        assert!(task.status == TaskStatus::Open, "Task is closed");
+        assert!(
+            task.hiring == Some(HiringStatus::Open),
+            "Task is not hiring"
+        );
+        assert!(
+            task.starts_at <= env::block_timestamp(),
+            "Task is not open for submissions yet"
+        );
+        assert!(
+            task.submit_available_until > env::block_timestamp(),
+            "Request is expired"
+        );
+        assert!(
+            self.applications.get(&application_id).is_none(),
+            "Already applied to this task"
+        );
+        self.internal_assert_invited(&task, &worker_id);
+        assert!(
+            cover_letter.len() <= self.app_config.maximum_cover_letter_length.into(),
+            "Cover letter too long"
+        );
+        assert!(
+            task.applications.len() < self.app_config.maximum_proposals_at_one_time.into(),
+            "Only accept {} applications at one time",
+            self.app_config.maximum_proposals_at_one_time
+        );
+
+        let mut worker = self.internal_get_account(&worker_id);
+        if let Some(requirements) = &task.requirements {
+            worker.assert_eligible(requirements);
+        }
         worker.current_jobs.insert(&task_id);
         self.internal_set_account(&worker_id, worker);
 
-        let proposal = Proposal {
+        let application = Application {
             account_id: worker_id,
-            submit_time: now,
-            proof_of_work: proof,
-            status: ProposalStatus::Pending,
+            cover_letter,
+            estimated_hours,
+            applied_at: env::block_timestamp(),
+            bond: env::attached_deposit(),
+            status: ApplicationStatus::Pending,
         };
+        self.applications.insert(&application_id, &application);
 
-        self.proposals.insert(&proposal_id, &proposal);
-
-        task.proposals.push(proposal_id);
+        task.applications.push(application_id);
         self.task_recores.insert(&task_id, &task);
+
+        self.finalize_storage_update(storage_update);
     }
 
     #[payable]
-    pub fn report_rejection(&mut self, task_id: String, report: String) {
+    pub fn report_rejection(
+        &mut self,
+        task_id: String,
+        report: Vec<Deliverable>,
+        milestone: Option<u8>,
+    ) {
+        self.internal_assert_deliverables(&report, None);
         let worker_id = env::predecessor_account_id();
         let (proposal_id, mut proposal) =
             self.internal_get_proposal(task_id.clone(), worker_id.clone());
 
-        match proposal.status {
+        match proposal.status_of(milestone).clone() {
             ProposalStatus::Rejected {
                 reason,
                 reject_at,
                 report_id,
             } => {
-                assert!(reason != "late", "Cannot report this reject reason");
+                assert!(
+                    reason != "late" && reason != "mismatch",
+                    "Cannot report this reject reason"
+                );
                 assert!(report_id.is_none(), "Reported this rejection");
                 assert!(
-                    reject_at + self.app_config.report_interval < env::block_timestamp(),
+                    env::block_timestamp() <= reject_at + self.app_config.report_interval,
                     "Not available to report this rejection"
                 );
 
                 // Update reports
-                let report_id = worker_id.clone() + "_" + &task_id;
+                let report_id =
+                    worker_id.clone() + "_" + &self.internal_gen_milestone_key(&task_id, milestone);
                 let report = Report {
                     report_id: report_id.clone(),
                     account_id: worker_id,
                     task_id,
+                    milestone,
                     report,
                     status: ReportStatus::Pending,
                 };
@@ -82,11 +289,14 @@ impl Dwork {
                 self.reports.insert(&report_id, &report);
                 
                 // Update proposal
-                proposal.status = ProposalStatus::Rejected {
-                    reason,
-                    reject_at,
-                    report_id: Some(report_id),
-                };
+                proposal.set_status(
+                    milestone,
+                    ProposalStatus::Rejected {
+                        reason,
+                        reject_at,
+                        report_id: Some(report_id),
+                    },
+                );
                 self.proposals.insert(&proposal_id, &proposal);
             }
             ProposalStatus::Approved => panic!("Proposal have been approved"),
@@ -94,17 +304,176 @@ impl Dwork {
         }
     }
 
-    pub fn claim(&mut self, task_id: TaskId) {
-        // let mut task = self.internal_get_task(&task_id);
+    pub fn claim(&mut self, task_id: TaskId, milestone: Option<u8>) {
+        let task = self.internal_get_task(&task_id);
         let worker_id = env::predecessor_account_id();
+        let storage_update = self.new_storage_update(worker_id.clone());
         let mut worker = self.internal_get_account(&worker_id);
-        let LockedBalance { amount, release_at } = worker.locked_balance.get(&task_id).expect("Locked Balance not found");
+        let locked_balance_id = self.internal_gen_milestone_key(&task_id, milestone);
+        let LockedBalance {
+            amount,
+            release_at,
+            token_id,
+        } = worker
+            .locked_balance
+            .get(&locked_balance_id)
+            .expect("Locked Balance not found");
         
         assert!(release_at < env::block_timestamp(), "This balance still be locked");
 
         worker.add_pos_point(self.app_config.sml_plus as u32);
-        worker.locked_balance.remove(&task_id);
-        self.internal_send(None, amount);
+        worker.locked_balance.remove(&locked_balance_id);
+        worker.add_earning(task_id.clone(), EarningKind::Payment, amount, token_id.clone());
+        // Milestone tasks are done with the last milestone
+        if milestone.map_or(0, |index| index as usize + 1) >= task.milestones.len() {
+            worker.current_jobs.remove(&task_id);
+            worker.completed_jobs.insert(&task_id);
+        }
+        self.internal_set_account(&worker_id, worker);
+        self.internal_payout(&worker_id, &token_id, amount);
+
+        self.finalize_storage_update(storage_update);
+    }
+}
+
+impl Dwork {
+    pub(crate) fn internal_submit_milestone(
+        &mut self,
+        task: &Task,
+        proposal_id: &ProposalId,
+        mut proposal: Proposal,
+        milestone: u8,
+        proof: Vec<Deliverable>,
+    ) {
+        let now = env::block_timestamp();
+        let next_milestone = task
+            .milestones
+            .get(milestone as usize)
+            .expect("Milestone not found");
+
+        assert_eq!(
+            env::attached_deposit(),
+            0,
+            "Submit bond is only required for the first milestone"
+        );
+        assert_eq!(
+            proposal.milestones.len(),
+            milestone as usize,
+            "Submit milestones in order"
+        );
+        assert!(
+            matches!(
+                proposal.milestones.last().map(|item| &item.status),
+                Some(ProposalStatus::Approved) | Some(ProposalStatus::ApprovedByAdmin { account_id: _ })
+            ),
+            "Previous milestone has not been approved"
+        );
+        assert!(next_milestone.deadline > now, "Milestone is expired");
+
+        proposal.proof_of_work = proof.clone();
+        proposal.milestones.push(MilestoneSubmission {
+            submit_time: now,
+            proof_of_work: proof,
+            status: ProposalStatus::Pending,
+        });
+        self.proposals.insert(proposal_id, &proposal);
+    }
+
+    // Checks the bond, the deadline and the seats, then creates the first submission
+    pub(crate) fn internal_new_proposal(
+        &mut self,
+        task_id: TaskId,
+        mut task: Task,
+        worker_id: AccountId,
+        proof: Vec<Deliverable>,
+        milestone: Option<u8>,
+        commitment: Option<Base64VecU8>,
+    ) {
+        let now = env::block_timestamp();
+        let proposal_id = self.internal_gen_proposal_id(task_id.clone(), worker_id.clone());
+
+        let bond_amount = if task.hiring.is_some() {
+            // Bond was already paid with the application
+            let (application_id, mut application) = self
+                .internal_get_application(task_id.clone(), worker_id.clone())
+                .expect("Only hired workers can submit to this task");
+            assert!(
+                application.status == ApplicationStatus::Hired,
+                "Only hired workers can submit to this task"
+            );
+            assert_eq!(env::attached_deposit(), 0, "Hired workers don't need to send a bond");
+
+            let bond_amount = application.bond;
+            application.bond = 0;
+            self.applications.insert(&application_id, &application);
+            bond_amount
+        } else {
+            // TODO: Allow user to use current balance
+            assert!(
+                env::attached_deposit() == self.app_config.submit_bond,
+                "Send exactly {:?} Near to submit",
+                self.app_config.submit_bond
+            );
+            env::attached_deposit()
+        };
+        assert!(task.starts_at <= now, "Task is not open for submissions yet");
+        assert!(task.submit_available_until > now, "Request is expired");
+        self.internal_assert_invited(&task, &worker_id);
+
+        assert!(self.proposals.get(&proposal_id).is_none(), "Already submitted this task");
+
+        assert!(
+            task.proposal_count(ProposalStatusKind::Approved) < task.max_participants.into(),
+            "Full approved participants"
+        );
+
+        let mut milestones = Vec::new();
+        if !task.milestones.is_empty() || milestone.is_some() {
+            let first = task.milestones.first().expect("This task has no milestones");
+            assert_eq!(milestone, Some(0), "Submit milestones in order");
+            assert!(first.deadline > now, "Milestone is expired");
+            milestones.push(MilestoneSubmission {
+                submit_time: now,
+                proof_of_work: proof.clone(),
+                status: ProposalStatus::Pending,
+            });
+        }
+
+        // Increase worker current task
+        let mut worker = self.internal_get_account(&worker_id);
+        if let Some(requirements) = &task.requirements {
+            worker.assert_eligible(requirements);
+        }
+        worker.current_jobs.insert(&task_id);
+        let worker_index = worker.proposals.len();
+        worker.proposals.push(&Some(proposal_id.clone()));
         self.internal_set_account(&worker_id, worker);
+
+        let proposal = Proposal {
+            task_id: task_id.clone(),
+            account_id: worker_id,
+            submit_time: now,
+            proof_of_work: proof,
+            status: if commitment.is_some() {
+                ProposalStatus::Committed
+            } else {
+                ProposalStatus::Pending
+            },
+            commitment,
+            bond: SubmitBond {
+                amount: bond_amount,
+                status: BondStatus::Locked,
+            },
+            milestones,
+            revisions: Vec::new(),
+            tipped: false,
+            worker_index,
+        };
+
+        self.internal_set_proposal(&mut task, &proposal_id, &proposal);
+
+        task.proposals.push(proposal_id);
+        self.task_recores.insert(&task_id, &task);
     }
+
 }